@@ -1,42 +1,127 @@
 use super::ast::*;
 use super::context::Context;
+use super::error::RuntimeError;
+use rusqlite::types::Value;
+use ordered_float::NotNan;
 use std::collections::HashMap;
-use std::collections::HashSet;
 
 #[derive(Clone, Debug)]
 pub enum DataType {
     Integer,
     Float,
     Symbol,
+    /// stored as 0/1
+    Boolean,
+}
+
+impl DataType {
+    /// the keywords declaring the column types of an edb
+    pub const KEYWORDS: [&'static str; 4] = ["int", "float", "sym", "bool"];
+
+    pub fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "int" => Some(DataType::Integer),
+            "float" => Some(DataType::Float),
+            "sym" => Some(DataType::Symbol),
+            "bool" => Some(DataType::Boolean),
+            _ => None,
+        }
+    }
+
+    /// column type used when creating a table
+    pub fn sql_type(&self) -> &'static str {
+        match self {
+            DataType::Integer => "INTEGER",
+            DataType::Symbol => "TEXT",
+            DataType::Float => "REAL",
+            DataType::Boolean => "BOOLEAN",
+        }
+    }
+
+    /// the storage classes of SQLite, as given by `typeof`, a value of this type is stored as,
+    /// an integer in a float column compares equal to the same real
+    pub fn storage_classes(&self) -> &'static [&'static str] {
+        match self {
+            DataType::Integer | DataType::Boolean => &["integer"],
+            DataType::Symbol => &["text"],
+            DataType::Float => &["real", "integer"],
+        }
+    }
+
+    /// the type of a constant written in a rule
+    pub fn of(constant: &Constant) -> Self {
+        match constant {
+            Constant::Integer(_) => DataType::Integer,
+            Constant::Float(_) => DataType::Float,
+            Constant::Symbol(_) => DataType::Symbol,
+            Constant::Boolean(_) => DataType::Boolean,
+        }
+    }
+
+    /// whether a constant of type `found` may be stored in a column of this type,
+    /// an integer is widened to a float in a float column, and any other value must have the type of its column
+    pub fn accepts(&self, found: &DataType) -> bool {
+        matches!((self, found),
+            (DataType::Integer, DataType::Integer)
+            | (DataType::Float, DataType::Integer | DataType::Float)
+            | (DataType::Symbol, DataType::Symbol)
+            | (DataType::Boolean, DataType::Boolean))
+    }
+
+    /// the type of a column typed by several rules, a column mixing integers and floats is a float column,
+    /// and none for any other pair of different types
+    fn merge(&self, other: &DataType) -> Option<Self> {
+        match (self, other) {
+            (DataType::Integer, DataType::Float) | (DataType::Float, DataType::Integer) => Some(DataType::Float),
+            (DataType::Integer, DataType::Integer)
+            | (DataType::Float, DataType::Float)
+            | (DataType::Symbol, DataType::Symbol)
+            | (DataType::Boolean, DataType::Boolean) => Some(self.clone()),
+            _ => None,
+        }
+    }
+
+    /// parse a textual field, e.g. from a csv file, into a sqlite value,
+    /// `NaN` is not a float, as a float constant is never NaN
+    pub fn parse_value(&self, field: &str) -> Option<Value> {
+        match self {
+            DataType::Integer => field.trim().parse::<i64>().ok().map(Value::Integer),
+            DataType::Float => field.trim().parse::<f64>().ok().filter(|value| !value.is_nan()).map(Value::Real),
+            DataType::Symbol => Some(Value::Text(field.to_string())),
+            DataType::Boolean => match field.trim() {
+                "true" | "1" => Some(Value::Integer(1)),
+                "false" | "0" => Some(Value::Integer(0)),
+                _ => None,
+            },
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct VarGroup {
     pub is_arith: bool,
+    pub is_negated: bool,
     pub clause_index: usize,
     pub term_indexes: Vec<usize>,
 }
 
-impl VarGroup {
-    pub fn contain_duplicate(&self) -> bool {
-        self.term_indexes.len() > 1
-    }
-}
-
 #[derive(Clone, Debug)]
 pub struct VarDict {
     pub head_dict: HashMap<String, Vec<usize>>,
-    pub clause_dict: HashMap<String, Vec<VarGroup>>
+    pub clause_dict: HashMap<String, Vec<VarGroup>>,
+    /// variables bound by an arithmetic assignment,
+    /// mapped to the index of the assigning clause and the assigned expression
+    pub assignments: HashMap<String, (usize, Arith)>,
 }
 
 impl VarDict {
     pub fn new(rule: &Rule) -> Self {
-        let mut clause_dict = HashMap::new();
-        let mut head_dict = HashMap::new();
+        let mut clause_dict: HashMap<String, Vec<VarGroup>> = HashMap::new();
+        let mut head_dict: HashMap<String, Vec<usize>> = HashMap::new();
         rule.head.terms.iter().enumerate().for_each(|(index, term)| {
             if let Some(var) = term.is_nontrivial_variable() {
                 head_dict.entry(var)
-                    .or_insert(Vec::new())
+                    .or_default()
                     .push(index);
             }
         });
@@ -45,26 +130,21 @@ impl VarDict {
                 Clause::Atom(atom) => {
                     let var_group_template = VarGroup {
                         is_arith: false,
+                        is_negated: atom.negation,
                         clause_index,
                         term_indexes: Vec::new(),
                     };
                     atom.terms.iter().enumerate().for_each(|(term_index, term)| {
+                        // a free variable is neither bound nor joined, even with another `_`
+                        if let Term::Variable(Variable::Free) = term {
+                            return;
+                        }
                         if let Some(var) = term.is_nontrivial_variable() {
                             let entry = clause_dict.entry(var)
-                                .or_insert(Vec::new());
-                            if entry.is_empty() {
-                                let mut var_group = var_group_template.clone();
-                                var_group.term_indexes.push(term_index);
-                                entry.push(var_group);
-                            } else {
-                                let mut same_clause = false;
-                                entry.iter_mut().for_each(|var_group| {
-                                    if var_group.clause_index == clause_index {
-                                        var_group.term_indexes.push(term_index);
-                                        same_clause = true;
-                                    }
-                                });
-                                if !same_clause {
+                                .or_default();
+                            match entry.iter_mut().find(|group| group.clause_index == clause_index) {
+                                Some(var_group) => var_group.term_indexes.push(term_index),
+                                None => {
                                     let mut var_group = var_group_template.clone();
                                     var_group.term_indexes.push(term_index);
                                     entry.push(var_group);
@@ -76,72 +156,142 @@ impl VarDict {
                 Clause::Arithmetic(arith) => {
                     let var_group_template = VarGroup {
                         is_arith: true,
+                        is_negated: false,
                         clause_index,
                         term_indexes: Vec::new(),
                     };
                     arith.get_leaves().iter().enumerate().for_each(|(term_index, term)| {
                         if let Some(var) = term.is_nontrivial_variable() {
                             let entry = clause_dict.entry(var)
-                                .or_insert(Vec::new());
-                            if entry.is_empty() {
-                                let mut var_group = var_group_template.clone();
-                                var_group.term_indexes.push(term_index);
-                                entry.push(var_group);
-                            } else {
-                                entry.iter_mut().for_each(|var_group| {
-                                    if var_group.clause_index == clause_index {
-                                        var_group.term_indexes.push(term_index);
-                                    }
-                                });
+                                .or_default();
+                            match entry.iter_mut().find(|group| group.clause_index == clause_index) {
+                                Some(var_group) => var_group.term_indexes.push(term_index),
+                                None => {
+                                    let mut var_group = var_group_template.clone();
+                                    var_group.term_indexes.push(term_index);
+                                    entry.push(var_group);
+                                }
                             }
                         }
                     });
                 }
             }
         });
-        Self { head_dict, clause_dict }
+        let mut var_dict = Self { head_dict, clause_dict, assignments: HashMap::new() };
+        // an assignment may use variables assigned by earlier clauses
+        rule.body.iter().enumerate().for_each(|(clause_index, clause)| {
+            if let Clause::Arithmetic(arith) = clause {
+                let is_bound = |var: &String| {
+                    var_dict.binding(var).is_some() || var_dict.assignments.contains_key(var)
+                };
+                if let Some((var, expr)) = arith.as_assignment(is_bound) {
+                    let expr = expr.clone();
+                    var_dict.assignments.insert(var, (clause_index, expr));
+                }
+            }
+        });
+        var_dict
     }
 
-    pub fn alloc(&self, var: &String) -> HashSet<(usize, usize)> {
-        let mut distinguished_vars = HashSet::new();
-        let groups = self.clause_dict.get(var).expect("Invalid var");
-        groups.iter().for_each(|group| {
-            group.term_indexes.iter().for_each(|term_index| {
-                distinguished_vars.insert((group.clause_index, *term_index));
-            });
-        });
-        distinguished_vars
+    pub fn is_assignment(&self, clause_index: usize) -> bool {
+        self.assignments.values().any(|(index, _)| *index == clause_index)
+    }
+
+    /// whether an expression has an aggregate, or a variable assigned one, so that it is a value of each group,
+    /// e.g. `count() > 5`, or `S > 4` after `S = sum(V)`, filters the groups of an aggregating rule
+    pub fn is_aggregated(&self, arith: &Arith) -> bool {
+        arith.is_aggregate() || arith.get_leaves().iter()
+            .filter_map(Term::is_nontrivial_variable)
+            .filter(|var| self.binding(var).is_none())
+            .any(|var| self.assignments.get(&var).is_some_and(|(_, expr)| self.is_aggregated(expr)))
+    }
+
+    /// a non-negated atom is a guard if each of its variables is bound by an earlier subgoal,
+    /// so it only filters the bindings of the other subgoals, and no column of it is read
+    pub fn is_guard(&self, clause_index: usize, atom: &Atom) -> bool {
+        !atom.negation && atom.terms.iter()
+            .filter_map(Term::is_nontrivial_variable)
+            .all(|var| self.binding(&var).is_some_and(|(index, _)| index != clause_index))
+    }
+
+    /// the first occurrence of `var` in a non-negated relational subgoal,
+    /// given as (clause_index, term_index).
+    /// every other occurrence of `var` is joined against this column.
+    pub fn binding(&self, var: &str) -> Option<(usize, usize)> {
+        self.clause_dict.get(var)?
+            .iter()
+            .find(|group| !group.is_arith && !group.is_negated)
+            .map(|group| (group.clause_index, group.term_indexes[0]))
     }
 }
 
+#[derive(Clone)]
 pub struct Analyzer {
     pub data_types: HashMap<String, Vec<DataType>>,
+    /// the column names declared by edbs, a column without a name is `None`
+    pub column_names: HashMap<String, Vec<Option<String>>>,
+    /// the [`VarDict`] of every rule of an idb, in the order of its rules,
+    /// built once rather than at every iteration of a recursive rule
+    pub var_dicts: HashMap<String, Vec<VarDict>>,
 }
 
 impl Analyzer {
     pub fn new() -> Self {
         Self {
             data_types: HashMap::new(),
+            column_names: HashMap::new(),
+            var_dicts: HashMap::new(),
         }
     }
 
-    pub fn type_inference(&mut self, context: &Context) {
-        context.edbs.iter().for_each(|(name, rule)| {
+    /// the names of the columns of a predicate, `column_0, column_1, ...` unless declared
+    pub fn columns(&self, predicate: &str) -> Vec<String> {
+        let arity = self.data_types.get(predicate).map_or(0, Vec::len);
+        let names = self.column_names.get(predicate);
+        (0..arity).map(|i| {
+            names.and_then(|names| names.get(i).cloned().flatten())
+                .unwrap_or_else(|| format!("column_{}", i))
+        }).collect()
+    }
+
+    pub fn type_inference(&mut self, context: &Context) -> Result<(), RuntimeError> {
+        for (name, rule) in context.edbs.iter() {
             let mut types = Vec::new();
-            rule.head.terms.iter().for_each(|term| {
-                if let Term::Constant(Constant::Symbol(type_)) = term {
-                    match type_.as_str() {
-                        "int" => types.push(DataType::Integer),
-                        "float" => types.push(DataType::Float),
-                        "sym" => types.push(DataType::Symbol),
-                        _ => panic!("Invalid type: {}", type_),
+            for (position, term) in rule.head.terms.iter().enumerate() {
+                let found = match term {
+                    Term::Constant(Constant::Symbol(keyword)) => keyword.clone(),
+                    term => term.to_string(),
+                };
+                let type_ = DataType::from_keyword(&found).ok_or_else(|| RuntimeError::UnknownColumnType {
+                    predicate: name.clone(),
+                    position,
+                    found,
+                })?;
+                types.push(type_);
+            }
+            self.data_types.insert(name.clone(), types);
+            if !rule.columns.is_empty() {
+                self.column_names.insert(name.clone(), rule.columns.clone());
+            }
+        }
+        // facts written in the source must have the declared types of their edb
+        for (name, facts) in context.facts.iter() {
+            let types = &self.data_types[name];
+            for fact in facts {
+                for (position, (term, expected)) in fact.terms.iter().zip(types.iter()).enumerate() {
+                    let Term::Constant(constant) = term else { continue };
+                    let found = DataType::of(constant);
+                    if !expected.accepts(&found) {
+                        return Err(RuntimeError::FactType {
+                            fact: fact.to_string(),
+                            position,
+                            expected: expected.clone(),
+                            found,
+                        });
                     }
-                } else {
-                    panic!("Invalid type: {:?}", term);
                 }
-            });
-            self.data_types.insert(name.clone(), types);
-        });
+            }
+        }
         // inference types for IDBs
         // IDBs' term types should be inferred from base cases
         let mut previous = context.edbs
@@ -149,52 +299,335 @@ impl Analyzer {
             .cloned()
             .collect::<Vec<String>>();
         let queue = context.ordered_idbs();
-        queue.iter().for_each(|name| {
+        for name in queue.iter() {
             let rules = context.idbs.get(name)
                 .expect("IDB should be present in context");
             let base_cases = rules.iter()
                 .filter(|rule| rule.is_base_case(&previous))
                 .collect::<Vec<&Rule>>();
-            base_cases.iter().for_each(|&rule| {
-                // for each term in the body, if it is distinguished
-                // then annotate it with the type of the declared type
-                let mut types = HashMap::new();
-                rule.body.iter().for_each(|clause| {
-                    if let Clause::Atom(atom) = clause {
-                        atom.terms.iter().enumerate().for_each(|(i, term)| {
-                            if let Term::Variable(Variable::Distinguished(var)) = term {
-                                let type_ = self.data_types.get(&atom.predicate)
-                                    .expect("EDB should be present in context")
-                                    .get(i)
-                                    .expect("Term should be present in EDB");
-                                // if var is already in types, then check if the type is the same
-                                // else insert the type
-                                types.entry(var).or_insert(type_);
+            for rule in base_cases {
+                let types = self.rule_types(rule)?;
+                // base cases typing the same predicate are merged column by column
+                let types = match self.data_types.get(name) {
+                    Some(previous) => previous.iter().zip(types.iter()).map(|(lhs, rhs)| {
+                        lhs.merge(rhs).ok_or_else(|| {
+                            let body = rule.body.iter().map(Clause::to_string).collect::<Vec<String>>();
+                            RuntimeError::TypeMismatch {
+                                predicate: name.clone(),
+                                expression: format!("{} :- {}", rule.head, body.join(", ")),
+                                lhs: lhs.clone(),
+                                rhs: rhs.clone(),
                             }
-                        });
+                        })
+                    }).collect::<Result<Vec<DataType>, RuntimeError>>()?,
+                    None => types,
+                };
+                self.data_types.insert(name.clone(), types);
+            }
+            if !self.data_types.contains_key(name) {
+                let types = self.recursive_types(rules)?;
+                self.data_types.insert(name.clone(), types);
+            }
+            previous.push(name.clone());
+        }
+        // views are typed as base cases over every edb and idb
+        for (name, rules) in context.queries.iter() {
+            for rule in rules.iter().filter(|rule| !rule.body.is_empty()) {
+                let types = self.rule_types(rule)?;
+                self.data_types.insert(name.clone(), types);
+            }
+        }
+        // arithmetic subgoals are checked once every predicate is typed
+        for rules in context.idbs.values().chain(context.queries.values()) {
+            for rule in rules.iter().filter(|rule| !rule.body.is_empty()) {
+                self.check_arithmetic(rule)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// build the [`VarDict`] of every rule of an idb, once the rules are not rewritten anymore
+    pub fn build_var_dicts(&mut self, context: &Context) {
+        self.var_dicts = context.idbs.iter()
+            .map(|(name, rules)| (name.clone(), rules.iter().map(VarDict::new).collect()))
+            .collect();
+    }
+
+    /// the types of the head terms of a rule, whose body predicates are already typed
+    fn rule_types(&self, rule: &Rule) -> Result<Vec<DataType>, RuntimeError> {
+        let types = self.variable_types(rule)?;
+        // check if all terms in the head have been annotated
+        rule.head.terms.iter().for_each(|term| {
+            if let Term::Variable(Variable::Distinguished(var)) = term {
+                if !types.contains_key(var) {
+                    panic!("Term `{}` in `{}` should be annotated", var, rule.head.predicate);
+                }
+            }
+        });
+        // convert types into vector following the order of the head terms,
+        // a constant in the head is typed by its own value
+        let types_vec = rule.head.terms.iter().map(|term| match term {
+            Term::Variable(Variable::Distinguished(var)) => {
+                let type_ = types.get(var)
+                    .expect("Term should be present in types");
+                type_.clone()
+            }
+            Term::Constant(constant) => DataType::of(constant),
+            _ => panic!("Term should be distinguished variable or constant"),
+        }).collect::<Vec<DataType>>();
+        Ok(types_vec)
+    }
+
+    /// the types of an idb without any base case, whose table is created even if it derives nothing.
+    /// a column is typed by the first rule typing it from the other subgoals, and as an integer otherwise
+    fn recursive_types(&self, rules: &[Rule]) -> Result<Vec<DataType>, RuntimeError> {
+        let mut columns = vec![None; rules[0].head.terms.len()];
+        for rule in rules {
+            let types = self.variable_types(rule)?;
+            for (column, term) in columns.iter_mut().zip(rule.head.terms.iter()) {
+                if column.is_none() {
+                    *column = match term {
+                        Term::Constant(constant) => Some(DataType::of(constant)),
+                        term => term.is_nontrivial_variable().and_then(|var| types.get(&var).cloned()),
+                    };
+                }
+            }
+        }
+        Ok(columns.into_iter().map(|column| column.unwrap_or(DataType::Integer)).collect())
+    }
+
+    /// the types of the variables of a rule, bound by a positive subgoal or assigned by arithmetic.
+    /// a subgoal on a predicate which is not typed yet leaves its variables untyped
+    fn variable_types(&self, rule: &Rule) -> Result<HashMap<String, DataType>, RuntimeError> {
+        // for each variable in a positive subgoal,
+        // annotate it with the type of the declared type
+        let mut types = HashMap::new();
+        rule.body.iter().for_each(|clause| {
+            if let Clause::Atom(atom) = clause {
+                if atom.negation {
+                    return;
+                }
+                let Some(atom_types) = self.data_types.get(&atom.predicate) else {
+                    return;
+                };
+                atom.terms.iter().enumerate().for_each(|(i, term)| {
+                    if let Some(var) = term.is_nontrivial_variable() {
+                        let type_ = atom_types.get(i)
+                            .expect("Term should be present in EDB");
+                        // if var is already in types, then check if the type is the same
+                        // else insert the type
+                        types.entry(var).or_insert(type_.clone());
                     }
                 });
-                // check if all terms in the head have been annotated
-                rule.head.terms.iter().for_each(|term| {
-                    if let Term::Variable(Variable::Distinguished(var)) = term {
-                        if !types.contains_key(var) {
-                            panic!("Term `{}` in `{}` should be annotated", var, rule.head.predicate);
+            }
+        });
+        // variables assigned by arithmetic are typed by the assigned expression,
+        // e.g. a float if any operand is a float, a boolean for a comparison or a symbol for a copied symbol
+        let mut assignments = VarDict::new(rule).assignments.into_iter().collect::<Vec<_>>();
+        assignments.sort_by_key(|(_, (clause_index, _))| *clause_index);
+        for (var, (_, expr)) in assignments {
+            let type_ = if let Operator::Aggregate(aggregate) = &expr.operator {
+                aggregate_type(rule, aggregate, &expr, &types)?
+            } else {
+                arith_type(&expr, &types).unwrap_or(DataType::Integer)
+            };
+            types.entry(var).or_insert(type_);
+        }
+        Ok(types)
+    }
+
+    /// every arithmetic subgoal in the body of a rule is well typed:
+    /// `+`, `-`, `*` and `/` apply to numbers, a comparison compares numbers with numbers,
+    /// or symbols and booleans with values of the same type, and only booleans are negated.
+    /// symbols are ordered lexicographically, and an assignment is checked by its assigned expression
+    fn check_arithmetic(&self, rule: &Rule) -> Result<(), RuntimeError> {
+        let types = self.variable_types(rule)?;
+        let var_dict = VarDict::new(rule);
+        for (clause_index, clause) in rule.body.iter().enumerate() {
+            if let Clause::Arithmetic(arith) = clause {
+                let arith = var_dict.assignments.values()
+                    .find(|(index, _)| *index == clause_index)
+                    .map_or(arith, |(_, expr)| expr);
+                check_arith(rule, arith, &types)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// rewrite every equality `A == B` of floats in the body of idbs and views
+    /// into `A - B < epsilon and B - A < epsilon`, so that floats computed differently match,
+    /// and every disequality `A != B` of floats into its negation.
+    /// an equality of integers, symbols or booleans, and an assignment, is left exact
+    pub fn approximate_float_equality(&self, context: &mut Context, epsilon: NotNan<f64>) -> Result<(), RuntimeError> {
+        for rules in context.idbs.values_mut().chain(context.queries.values_mut()) {
+            for rule in rules.iter_mut().filter(|rule| !rule.body.is_empty()) {
+                let types = self.variable_types(rule)?;
+                let var_dict = VarDict::new(rule);
+                for (clause_index, clause) in rule.body.iter_mut().enumerate() {
+                    if let Clause::Arithmetic(arith) = clause {
+                        if !var_dict.is_assignment(clause_index) {
+                            approximate(arith, &types, epsilon);
                         }
                     }
-                });
-                // convert types into vector following the order of the head terms
-                let types_vec = rule.head.terms.iter().map(|term| {
-                    if let Term::Variable(Variable::Distinguished(var)) = term {
-                        let type_ = types.get(var)
-                            .expect("Term should be present in types").clone();
-                        type_.to_owned()
-                    } else {
-                        panic!("Term should be distinguished variable");
-                    }
-                }).collect::<Vec<DataType>>();
-                self.data_types.insert(rule.head.predicate.clone(), types_vec);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// rewrite the float equalities and disequalities of an arithmetic subgoal,
+/// including those under a conjunction, a disjunction or a negation
+fn approximate(arith: &mut Arith, types: &HashMap<String, DataType>, epsilon: NotNan<f64>) {
+    let is_float = |operand: &Option<Box<Arith>>| operand.as_ref()
+        .and_then(|operand| arith_type(operand, types))
+        .is_some_and(|type_| matches!(type_, DataType::Float));
+    match arith.operator {
+        Operator::And | Operator::Or | Operator::Neg => {
+            for operand in [&mut arith.lhs, &mut arith.rhs].into_iter().flatten() {
+                approximate(operand, types, epsilon);
+            }
+        }
+        Operator::Unifier | Operator::Disunifier if is_float(&arith.lhs) || is_float(&arith.rhs) => {
+            let (Some(lhs), Some(rhs)) = (arith.lhs.take(), arith.rhs.take()) else {
+                return;
+            };
+            let within = |lhs: Box<Arith>, rhs: Box<Arith>| Arith {
+                operator: Operator::Less,
+                lhs: Some(Box::new(Arith { operator: Operator::Sub, lhs: Some(lhs), rhs: Some(rhs) })),
+                rhs: Some(Box::new(Arith {
+                    operator: Operator::Leaf(Term::Constant(Constant::Float(epsilon))),
+                    lhs: None,
+                    rhs: None,
+                })),
+            };
+            let equal = Arith {
+                operator: Operator::And,
+                lhs: Some(Box::new(within(lhs.clone(), rhs.clone()))),
+                rhs: Some(Box::new(within(rhs, lhs))),
+            };
+            *arith = match arith.operator {
+                Operator::Disunifier => Arith { operator: Operator::Neg, lhs: None, rhs: Some(Box::new(equal)) },
+                _ => equal,
+            };
+        }
+        _ => {}
+    }
+}
+
+/// check an arithmetic expression and every expression nested in it,
+/// an operand whose type is unknown is left unchecked
+fn check_arith(rule: &Rule, arith: &Arith, types: &HashMap<String, DataType>) -> Result<(), RuntimeError> {
+    for operand in [&arith.lhs, &arith.rhs].into_iter().flatten() {
+        check_arith(rule, operand, types)?;
+    }
+    let type_of = |operand: &Option<Box<Arith>>| operand.as_ref().and_then(|operand| arith_type(operand, types));
+    let is_numeric = |type_: &DataType| matches!(type_, DataType::Integer | DataType::Float);
+    let comparable = |lhs: &DataType, rhs: &DataType| matches!(
+        (lhs, rhs),
+        (DataType::Integer | DataType::Float, DataType::Integer | DataType::Float)
+        | (DataType::Symbol, DataType::Symbol)
+        | (DataType::Boolean, DataType::Boolean)
+    );
+    let (lhs, rhs) = match (&arith.operator, type_of(&arith.lhs), type_of(&arith.rhs)) {
+        // only a boolean, e.g. a comparison, is negated by `!`
+        (Operator::Neg, _, Some(found)) if !matches!(found, DataType::Boolean) => {
+            return Err(RuntimeError::NotBoolean {
+                predicate: rule.head.predicate.clone(),
+                expression: arith.to_string(),
+                found,
             });
-            previous.push(name.clone());
-        });
+        }
+        (Operator::Sub | Operator::Function(Function::Abs), None, Some(found)) if arith.lhs.is_none() && !is_numeric(&found) => {
+            return Err(RuntimeError::NotNumeric {
+                predicate: rule.head.predicate.clone(),
+                expression: arith.to_string(),
+                found,
+            });
+        }
+        // every constant of a membership is compared with the lhs
+        (Operator::In(constants), Some(lhs), None) => {
+            match constants.iter().map(DataType::of).find(|rhs| !comparable(&lhs, rhs)) {
+                Some(rhs) => (lhs, rhs),
+                None => return Ok(()),
+            }
+        }
+        (_, Some(lhs), Some(rhs)) => (lhs, rhs),
+        _ => return Ok(()),
+    };
+    let compatible = match arith.operator {
+        Operator::Add | Operator::Sub | Operator::Mul | Operator::Div | Operator::Function(_) => {
+            is_numeric(&lhs) && is_numeric(&rhs)
+        }
+        Operator::Mod => matches!((&lhs, &rhs), (DataType::Integer, DataType::Integer)),
+        Operator::Unifier
+        | Operator::Disunifier
+        | Operator::Less
+        | Operator::LessEqual
+        | Operator::Greater
+        | Operator::GreaterEqual
+        | Operator::In(_) => comparable(&lhs, &rhs),
+        _ => true,
+    };
+    if compatible {
+        return Ok(());
+    }
+    Err(RuntimeError::TypeMismatch {
+        predicate: rule.head.predicate.clone(),
+        expression: arith.to_string(),
+        lhs,
+        rhs,
+    })
+}
+
+/// the type of an arithmetic expression, none if a variable in it is not typed
+fn arith_type(arith: &Arith, types: &HashMap<String, DataType>) -> Option<DataType> {
+    match &arith.operator {
+        Operator::Leaf(Term::Constant(constant)) => Some(DataType::of(constant)),
+        Operator::Leaf(term) => term.is_nontrivial_variable().and_then(|var| types.get(&var).cloned()),
+        Operator::Mod => Some(DataType::Integer),
+        Operator::Add | Operator::Sub | Operator::Mul | Operator::Div | Operator::Function(_) => {
+            let operands = [&arith.lhs, &arith.rhs].into_iter()
+                .flatten()
+                .map(|operand| arith_type(operand, types))
+                .collect::<Option<Vec<DataType>>>()?;
+            if operands.iter().any(|type_| matches!(type_, DataType::Float)) {
+                Some(DataType::Float)
+            } else {
+                Some(DataType::Integer)
+            }
+        }
+        Operator::Aggregate(Aggregate::Count | Aggregate::CountDistinct) => Some(DataType::Integer),
+        Operator::Aggregate(Aggregate::Avg) => Some(DataType::Float),
+        Operator::Aggregate(_) => arith.rhs.as_ref().and_then(|operand| arith_type(operand, types)),
+        _ => Some(DataType::Boolean),
+    }
+}
+
+/// count and count_distinct are integers, avg is a float as SQLite's AVG always returns a real,
+/// sum, min and max keep the type of the aggregated variable
+fn aggregate_type(
+    rule: &Rule,
+    aggregate: &Aggregate,
+    expr: &Arith,
+    types: &HashMap<String, DataType>
+) -> Result<DataType, RuntimeError> {
+    let var = match expr.get_leaves().first().and_then(|leaf| leaf.is_nontrivial_variable()) {
+        Some(var) => var,
+        None => return Ok(DataType::Integer),
+    };
+    let type_ = types.get(&var).ok_or_else(|| RuntimeError::UnboundVariable {
+        predicate: rule.head.predicate.clone(),
+        var: var.clone(),
+    })?;
+    match (aggregate, type_) {
+        (Aggregate::Sum | Aggregate::Avg, DataType::Symbol | DataType::Boolean) => Err(RuntimeError::AggregateType {
+            predicate: rule.head.predicate.clone(),
+            aggregate: aggregate.to_string(),
+            var,
+        }),
+        (Aggregate::Avg, _) => Ok(DataType::Float),
+        (Aggregate::CountDistinct, _) => Ok(DataType::Integer),
+        _ => Ok(type_.clone()),
     }
 }
\ No newline at end of file
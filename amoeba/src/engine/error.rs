@@ -0,0 +1,253 @@
+use crate::syntax::error::{ContextError, SourceError};
+use super::analysis::DataType;
+use std::error::Error;
+use std::fmt::Display;
+
+/// [`RuntimeError`] is raised while loading the database or evaluating the program.
+#[derive(Debug)]
+pub enum RuntimeError {
+    Io(std::io::Error),
+    Source(SourceError),
+    /// an edb declared with @input has no table in the database
+    MissingEdb(String),
+    /// tuples are supplied for a predicate which is not an edb of the program
+    UnknownEdb(String),
+    /// a predicate to store on disk is not an idb of the program
+    UnknownIdb(String),
+    /// idbs are to be stored on disk by a runtime without a database file beside which to store them
+    DiskWithoutDatabase,
+    /// the query selected to be evaluated is not an @output predicate
+    UnknownQuery {
+        query: String,
+        available: Vec<String>,
+    },
+    /// the table in the database does not have the declared arity
+    ArityMismatch {
+        table: String,
+        expected: usize,
+        actual: usize,
+    },
+    /// a predicate starts with a prefix reserved for the tables of the evaluation
+    ReservedPrefix {
+        predicate: String,
+        prefix: String,
+    },
+    /// a column of an edb is declared with an unknown type keyword
+    UnknownColumnType {
+        predicate: String,
+        position: usize,
+        found: String,
+    },
+    /// a variable in the head does not appear in any non-negated relational subgoal
+    UnboundHeadVariable {
+        predicate: String,
+        var: String,
+    },
+    /// a variable in an arithmetic subgoal does not appear in any non-negated relational subgoal
+    UnboundVariable {
+        predicate: String,
+        var: String,
+    },
+    /// an aggregate is applied to a variable of an unsupported type
+    AggregateType {
+        predicate: String,
+        aggregate: String,
+        var: String,
+    },
+    /// an arithmetic operation or a comparison in the body of a rule applies to values of incompatible types
+    TypeMismatch {
+        predicate: String,
+        expression: String,
+        lhs: DataType,
+        rhs: DataType,
+    },
+    /// `!` is applied to a value which is not a boolean in the body of a rule
+    NotBoolean {
+        predicate: String,
+        expression: String,
+        found: DataType,
+    },
+    /// unary `-` or abs is applied to a value which is not a number in the body of a rule
+    NotNumeric {
+        predicate: String,
+        expression: String,
+        found: DataType,
+    },
+    /// a value of a tuple given for an edb does not have the declared type of its column
+    TupleType {
+        predicate: String,
+        position: usize,
+        expected: DataType,
+        found: DataType,
+    },
+    /// a fact written in the source gives a column of its edb a value of another type than declared
+    FactType {
+        fact: String,
+        position: usize,
+        expected: DataType,
+        found: DataType,
+    },
+    /// a recursive predicate has not reached a fixpoint within the iteration limit
+    IterationLimitExceeded {
+        predicate: String,
+        limit: usize,
+    },
+    /// the tuple to trace is not a tuple of a predicate of the program
+    InvalidTrace {
+        trace: String,
+        reason: String,
+    },
+    /// the atom to ask is not an atom over a materialized predicate of the program
+    InvalidAsk {
+        ask: String,
+        reason: String,
+    },
+    /// a rule divides by the constant zero
+    DivisionByZero(String),
+    /// a rule uses a feature the chosen evaluation backend does not support
+    UnsupportedByBackend {
+        predicate: String,
+        feature: String,
+    },
+    /// a csv file of an edb cannot be read
+    Csv {
+        file: String,
+        error: csv::Error,
+    },
+    /// a row of a csv file does not match the declared edb
+    CsvRow {
+        file: String,
+        line: u64,
+        reason: String,
+    },
+    /// a value of the table of an edb, in the row at this position, cannot be coerced to its declared type
+    Coercion {
+        table: String,
+        row: usize,
+        reason: String,
+    },
+    /// a json file of an edb cannot be read, or is not an array of records
+    Json {
+        file: String,
+        reason: String,
+    },
+    /// a record of a json file, at this index of its array, does not match the declared edb
+    JsonRecord {
+        file: String,
+        record: usize,
+        reason: String,
+    },
+    Sqlite(rusqlite::Error),
+}
+
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuntimeError::Io(error) => write!(f, "{}", error),
+            RuntimeError::Source(error) => write!(f, "{}", error),
+            RuntimeError::MissingEdb(table) => write!(f, "EDB {} is not present in database", table),
+            RuntimeError::UnknownEdb(table) => write!(f, "{} is not an EDB of the program", table),
+            RuntimeError::UnknownIdb(table) => write!(f, "{} is not an IDB of the program", table),
+            RuntimeError::DiskWithoutDatabase => {
+                write!(f, "IDBs cannot be stored on disk by a runtime built from facts, as it has no database file")
+            }
+            RuntimeError::UnknownQuery { query, available } if available.is_empty() => {
+                write!(f, "{} is not an @output predicate, the program has no query", query)
+            }
+            RuntimeError::UnknownQuery { query, available } => {
+                write!(f, "{} is not an @output predicate, the queries are {}", query, available.join(", "))
+            }
+            RuntimeError::ArityMismatch { table, expected, actual } => {
+                write!(f, "Table {} has {} columns, but {} are declared", table, actual, expected)
+            }
+            RuntimeError::ReservedPrefix { predicate, prefix } => {
+                write!(f, "Predicate {} starts with {}, which is reserved for the tables of the evaluation", predicate, prefix)
+            }
+            RuntimeError::UnknownColumnType { predicate, position, found } => {
+                write!(f, "Column {} of {} is declared as `{}`, but the type should be one of {}",
+                    position,
+                    predicate,
+                    found,
+                    DataType::KEYWORDS.join(", ")
+                )
+            }
+            RuntimeError::UnboundHeadVariable { predicate, var } => {
+                write!(f, "Variable {} in the head of {} is not assigned", var, predicate)
+            }
+            RuntimeError::UnboundVariable { predicate, var } => {
+                write!(f, "Variable {} in the body of {} is not bound", var, predicate)
+            }
+            RuntimeError::AggregateType { predicate, aggregate, var } => {
+                write!(f, "Cannot {} over non-numeric variable {} in the body of {}", aggregate, var, predicate)
+            }
+            RuntimeError::TypeMismatch { predicate, expression, lhs, rhs } => {
+                write!(f, "{:?} and {:?} are incompatible in `{}` in the body of {}", lhs, rhs, expression, predicate)
+            }
+            RuntimeError::NotBoolean { predicate, expression, found } => {
+                write!(f, "Cannot negate {:?} with ! in `{}` in the body of {}", found, expression, predicate)
+            }
+            RuntimeError::NotNumeric { predicate, expression, found } => {
+                write!(f, "{:?} is not a number in `{}` in the body of {}", found, expression, predicate)
+            }
+            RuntimeError::TupleType { predicate, position, expected, found } => {
+                write!(f, "Column {} of {} is {:?}, but a tuple gives it a {:?}", position, predicate, expected, found)
+            }
+            RuntimeError::FactType { fact, position, expected, found } => {
+                write!(f, "The fact {} gives column {} a {:?}, but it is declared {:?}", fact, position, found, expected)
+            }
+            RuntimeError::IterationLimitExceeded { predicate, limit } => {
+                write!(f, "{} has not reached a fixpoint after {} iterations", predicate, limit)
+            }
+            RuntimeError::InvalidTrace { trace, reason } => write!(f, "Cannot trace {}: {}", trace, reason),
+            RuntimeError::InvalidAsk { ask, reason } => write!(f, "Cannot ask {}: {}", ask, reason),
+            RuntimeError::DivisionByZero(predicate) => write!(f, "The rule of {} divides by zero", predicate),
+            RuntimeError::UnsupportedByBackend { predicate, feature } => {
+                write!(f, "The rule of {} uses {}, which is not supported by this backend", predicate, feature)
+            }
+            RuntimeError::Csv { file, error } => write!(f, "{}: {}", file, error),
+            RuntimeError::CsvRow { file, line, reason } => write!(f, "{}:{}: {}", file, line, reason),
+            RuntimeError::Coercion { table, row, reason } => {
+                write!(f, "Row {} of {} cannot be coerced to the declared types: {}", row, table, reason)
+            }
+            RuntimeError::Json { file, reason } => write!(f, "{}: {}", file, reason),
+            RuntimeError::JsonRecord { file, record, reason } => write!(f, "{}: record {}: {}", file, record, reason),
+            RuntimeError::Sqlite(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl Error for RuntimeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RuntimeError::Io(error) => Some(error),
+            RuntimeError::Source(error) => Some(error),
+            RuntimeError::Csv { error, .. } => Some(error),
+            RuntimeError::Sqlite(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for RuntimeError {
+    fn from(error: std::io::Error) -> Self {
+        RuntimeError::Io(error)
+    }
+}
+
+impl From<SourceError> for RuntimeError {
+    fn from(error: SourceError) -> Self {
+        RuntimeError::Source(error)
+    }
+}
+
+impl From<ContextError> for RuntimeError {
+    fn from(error: ContextError) -> Self {
+        RuntimeError::Source(SourceError::Context(error))
+    }
+}
+
+impl From<rusqlite::Error> for RuntimeError {
+    fn from(error: rusqlite::Error) -> Self {
+        RuntimeError::Sqlite(error)
+    }
+}
@@ -0,0 +1,206 @@
+use super::ast::Rule;
+use super::analysis::{DataType, VarDict};
+use super::error::RuntimeError;
+use super::runtime::{Profile, Statement};
+use std::time::Instant;
+
+/// [`Mode`] decides which relation each atom in the body of a rule reads.
+#[derive(Clone, Copy, Debug)]
+pub enum Mode {
+    /// every atom reads the full relation of its predicate
+    Base,
+    /// the atom at this clause index reads the delta relation of its predicate,
+    /// every other atom reads the full relation
+    Delta(usize),
+}
+
+impl Mode {
+    /// the relation read by the atom at `clause_index`
+    pub fn relation(&self, clause_index: usize, predicate: &str) -> String {
+        match self {
+            Mode::Delta(index) if *index == clause_index => delta_relation(predicate),
+            _ => predicate.to_string(),
+        }
+    }
+}
+
+/// the prefixes of the scratch relations of recursive predicates, of idbs written back by
+/// [`Writeback::Idbs`](super::Writeback::Idbs), and of the predicates introduced by
+/// [`Options::magic`](super::Options::magic), which no predicate of a program may start with
+pub const RESERVED_PREFIXES: [&str; 6] = ["delta_", "temp_", "idb_", "magic_", "seed_", "adorned_"];
+
+/// the tuples derived by the last iteration of a recursive predicate
+pub fn delta_relation(predicate: &str) -> String {
+    format!("delta_{}", predicate)
+}
+
+/// the tuples derived by the current iteration of a recursive predicate
+pub fn temp_relation(predicate: &str) -> String {
+    format!("temp_{}", predicate)
+}
+
+/// [`Evaluator`] stores relations and performs the operations of semi-naive evaluation on them.
+/// a relation is named by its predicate, the delta and the newly derived tuples
+/// of a recursive predicate `p` are the relations `delta_p` and `temp_p`.
+pub trait Evaluator {
+    /// create an empty relation with the given column types, unless it is present
+    fn create_relation(&mut self, name: &str, types: &[DataType]) -> Result<(), RuntimeError>;
+
+    /// create the relation `to` holding every tuple of `from`
+    fn copy_relation(&mut self, from: &str, to: &str) -> Result<(), RuntimeError>;
+
+    fn drop_relation(&mut self, name: &str) -> Result<(), RuntimeError>;
+
+    /// insert the head tuples derived by a rule into `target`, ignoring those already present.
+    /// the mode decides whether an atom reads the delta relation of its predicate
+    fn select_into(&mut self, rule: &Rule, var_dict: &VarDict, target: &str, mode: Mode) -> Result<(), RuntimeError>;
+
+    /// insert the tuples of `source` into `target`, ignoring those already present
+    fn insert_distinct(&mut self, target: &str, source: &str) -> Result<(), RuntimeError>;
+
+    /// replace the tuples of `target` by those of `left` missing from `right`,
+    /// all three relations have `arity` columns
+    fn diff(&mut self, target: &str, left: &str, right: &str, arity: usize) -> Result<(), RuntimeError>;
+
+    fn count(&mut self, name: &str) -> Result<usize, RuntimeError>;
+
+    /// called once before the fixpoint loop of a recursive rule
+    fn prepare(&mut self, _rule: &Rule, _var_dict: &VarDict) -> Result<(), RuntimeError> {
+        Ok(())
+    }
+
+    /// the operations of an iteration are applied at once, between `begin` and `commit`
+    fn begin(&mut self) -> Result<(), RuntimeError> {
+        Ok(())
+    }
+
+    fn commit(&mut self) -> Result<(), RuntimeError> {
+        Ok(())
+    }
+
+    /// discard the operations since `begin`, if an iteration is not committed
+    fn rollback(&mut self) -> Result<(), RuntimeError> {
+        Ok(())
+    }
+}
+
+/// [`ScratchRelations`] owns the delta and temp relations of a recursive predicate.
+/// they are dropped by [`ScratchRelations::drop_all`] after the fixpoint is reached,
+/// or when the guard goes out of scope on an error, after rolling back an uncommitted iteration,
+/// so that an interrupted evaluation leaves no scratch relation behind
+pub struct ScratchRelations<'a> {
+    pub evaluator: &'a mut dyn Evaluator,
+    relations: Vec<String>,
+}
+
+impl<'a> ScratchRelations<'a> {
+    pub fn new(evaluator: &'a mut dyn Evaluator) -> Self {
+        Self { evaluator, relations: Vec::new() }
+    }
+
+    /// create the relation `to` holding every tuple of `from`, dropped with the guard
+    pub fn copy_relation(&mut self, from: &str, to: &str) -> Result<(), RuntimeError> {
+        self.evaluator.copy_relation(from, to)?;
+        self.relations.push(to.to_string());
+        Ok(())
+    }
+
+    /// create an empty relation with the given column types, dropped with the guard
+    pub fn create_relation(&mut self, name: &str, types: &[DataType]) -> Result<(), RuntimeError> {
+        self.evaluator.create_relation(name, types)?;
+        self.relations.push(name.to_string());
+        Ok(())
+    }
+
+    pub fn drop_all(&mut self) -> Result<(), RuntimeError> {
+        while let Some(name) = self.relations.pop() {
+            self.evaluator.drop_relation(&name)?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for ScratchRelations<'_> {
+    fn drop(&mut self) {
+        if self.relations.is_empty() {
+            return;
+        }
+        // errors are already being reported, cleaning up is best effort
+        let _ = self.evaluator.rollback();
+        let _ = self.drop_all();
+    }
+}
+
+/// [`Profiled`] performs the operations of an [`Evaluator`],
+/// and records the time each of them takes in a [`Profile`]
+pub struct Profiled<'a> {
+    evaluator: &'a mut dyn Evaluator,
+    pub profile: Profile,
+}
+
+impl<'a> Profiled<'a> {
+    pub fn new(evaluator: &'a mut dyn Evaluator) -> Self {
+        Self { evaluator, profile: Profile::default() }
+    }
+
+    fn time<T>(
+        &mut self,
+        statement: Statement,
+        operation: impl FnOnce(&mut dyn Evaluator) -> Result<T, RuntimeError>
+    ) -> Result<T, RuntimeError> {
+        let now = Instant::now();
+        let result = operation(&mut *self.evaluator);
+        self.profile.record(statement, now.elapsed());
+        result
+    }
+}
+
+impl Evaluator for Profiled<'_> {
+    fn create_relation(&mut self, name: &str, types: &[DataType]) -> Result<(), RuntimeError> {
+        self.time(Statement::Create, |evaluator| evaluator.create_relation(name, types))
+    }
+
+    fn copy_relation(&mut self, from: &str, to: &str) -> Result<(), RuntimeError> {
+        self.time(Statement::Create, |evaluator| evaluator.copy_relation(from, to))
+    }
+
+    fn drop_relation(&mut self, name: &str) -> Result<(), RuntimeError> {
+        self.time(Statement::Create, |evaluator| evaluator.drop_relation(name))
+    }
+
+    fn select_into(&mut self, rule: &Rule, var_dict: &VarDict, target: &str, mode: Mode) -> Result<(), RuntimeError> {
+        let statement = match mode {
+            Mode::Base => Statement::BaseInsert,
+            Mode::Delta(_) => Statement::DeltaJoin,
+        };
+        self.time(statement, |evaluator| evaluator.select_into(rule, var_dict, target, mode))
+    }
+
+    fn insert_distinct(&mut self, target: &str, source: &str) -> Result<(), RuntimeError> {
+        self.time(Statement::Merge, |evaluator| evaluator.insert_distinct(target, source))
+    }
+
+    fn diff(&mut self, target: &str, left: &str, right: &str, arity: usize) -> Result<(), RuntimeError> {
+        self.time(Statement::Diff, |evaluator| evaluator.diff(target, left, right, arity))
+    }
+
+    fn count(&mut self, name: &str) -> Result<usize, RuntimeError> {
+        self.time(Statement::Count, |evaluator| evaluator.count(name))
+    }
+
+    fn prepare(&mut self, rule: &Rule, var_dict: &VarDict) -> Result<(), RuntimeError> {
+        self.time(Statement::Create, |evaluator| evaluator.prepare(rule, var_dict))
+    }
+
+    fn begin(&mut self) -> Result<(), RuntimeError> {
+        self.time(Statement::Transaction, |evaluator| evaluator.begin())
+    }
+
+    fn commit(&mut self) -> Result<(), RuntimeError> {
+        self.time(Statement::Transaction, |evaluator| evaluator.commit())
+    }
+
+    fn rollback(&mut self) -> Result<(), RuntimeError> {
+        self.time(Statement::Transaction, |evaluator| evaluator.rollback())
+    }
+}
@@ -0,0 +1,143 @@
+use super::ast::*;
+use super::analysis::{DataType, VarDict};
+use super::evaluator::{Evaluator, Mode};
+use super::error::RuntimeError;
+use std::collections::{HashMap, HashSet};
+
+/// [`MemoryEvaluator`] keeps every relation as a set of tuples in memory,
+/// and joins the atoms of a body by nested loops from left to right.
+/// it avoids the overhead of SQLite for small workloads,
+/// but does not evaluate arithmetic subgoals or aggregates.
+#[derive(Default)]
+pub struct MemoryEvaluator {
+    relations: HashMap<String, HashSet<Tuple>>,
+}
+
+/// the values bound to the variables of a body
+type Binding = HashMap<String, Constant>;
+
+impl MemoryEvaluator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// insert tuples into a relation, creating it if not present
+    pub fn insert(&mut self, name: &str, tuples: Vec<Tuple>) {
+        self.relations.entry(name.to_string()).or_default().extend(tuples);
+    }
+
+    pub fn tuples(&self, name: &str) -> Vec<Tuple> {
+        self.relations.get(name).map_or(Vec::new(), |tuples| tuples.iter().cloned().collect())
+    }
+
+    fn relation(&self, name: &str) -> Result<&HashSet<Tuple>, RuntimeError> {
+        self.relations.get(name).ok_or_else(|| RuntimeError::MissingEdb(name.to_string()))
+    }
+}
+
+impl Evaluator for MemoryEvaluator {
+    fn create_relation(&mut self, name: &str, _types: &[DataType]) -> Result<(), RuntimeError> {
+        self.relations.entry(name.to_string()).or_default();
+        Ok(())
+    }
+
+    fn copy_relation(&mut self, from: &str, to: &str) -> Result<(), RuntimeError> {
+        let tuples = self.relation(from)?.clone();
+        self.relations.insert(to.to_string(), tuples);
+        Ok(())
+    }
+
+    fn drop_relation(&mut self, name: &str) -> Result<(), RuntimeError> {
+        self.relations.remove(name);
+        Ok(())
+    }
+
+    fn select_into(&mut self, rule: &Rule, _var_dict: &VarDict, target: &str, mode: Mode) -> Result<(), RuntimeError> {
+        let mut bindings = vec![Binding::new()];
+        for (clause_index, clause) in rule.body.iter().enumerate() {
+            match clause {
+                Clause::Atom(atom) if !atom.negation => {
+                    let relation = self.relation(&mode.relation(clause_index, &atom.predicate))?;
+                    bindings = bindings.iter()
+                        .flat_map(|binding| relation.iter().filter_map(|tuple| unify(atom, tuple, binding)))
+                        .collect();
+                }
+                Clause::Atom(_) => {}
+                Clause::Arithmetic(_) => return Err(RuntimeError::UnsupportedByBackend {
+                    predicate: rule.head.predicate.clone(),
+                    feature: "arithmetic".to_string(),
+                }),
+            }
+        }
+        // negated subgoals filter the bindings of the positive ones,
+        // a variable only occurring in a negated subgoal matches any value
+        for clause in rule.body.iter() {
+            if let Clause::Atom(atom) = clause {
+                if atom.negation {
+                    let relation = self.relation(&atom.predicate)?;
+                    bindings.retain(|binding| !relation.iter().any(|tuple| unify(atom, tuple, binding).is_some()));
+                }
+            }
+        }
+        let mut tuples = Vec::new();
+        for binding in bindings {
+            let tuple = rule.head.terms.iter().map(|term| match term {
+                Term::Constant(constant) => Ok(constant.clone()),
+                Term::Variable(_) => term.is_nontrivial_variable()
+                    .and_then(|var| binding.get(&var).cloned())
+                    .ok_or_else(|| RuntimeError::UnboundHeadVariable {
+                        predicate: rule.head.predicate.clone(),
+                        var: term.to_string(),
+                    }),
+            }).collect::<Result<Tuple, RuntimeError>>()?;
+            tuples.push(tuple);
+        }
+        self.insert(target, tuples);
+        Ok(())
+    }
+
+    fn insert_distinct(&mut self, target: &str, source: &str) -> Result<(), RuntimeError> {
+        let tuples = self.tuples(source);
+        self.insert(target, tuples);
+        Ok(())
+    }
+
+    fn diff(&mut self, target: &str, left: &str, right: &str, _arity: usize) -> Result<(), RuntimeError> {
+        let right = self.relation(right)?;
+        let tuples = self.relation(left)?
+            .iter()
+            .filter(|tuple| !right.contains(*tuple))
+            .cloned()
+            .collect();
+        self.relations.insert(target.to_string(), tuples);
+        Ok(())
+    }
+
+    fn count(&mut self, name: &str) -> Result<usize, RuntimeError> {
+        Ok(self.relation(name)?.len())
+    }
+}
+
+/// extend a binding so that the atom matches the tuple, if they agree on every term
+fn unify(atom: &Atom, tuple: &Tuple, binding: &Binding) -> Option<Binding> {
+    let mut binding = binding.clone();
+    for (term, value) in atom.terms.iter().zip(tuple.iter()) {
+        match term {
+            Term::Constant(constant) if constant != value => return None,
+            Term::Constant(_) => {}
+            Term::Variable(Variable::Free) => {}
+            Term::Variable(_) => {
+                let var = term.is_nontrivial_variable()
+                    .expect("Variable should be nontrivial");
+                match binding.get(&var) {
+                    Some(bound) if bound != value => return None,
+                    Some(_) => {}
+                    None => {
+                        binding.insert(var, value.clone());
+                    }
+                }
+            }
+        }
+    }
+    Some(binding)
+}
@@ -1,18 +1,70 @@
-use super::syntax::{context, ast};
-use super::syntax::parse;
+use super::syntax::{context, ast, magic};
+use super::syntax::{parse_file, parse_atom};
 use colored::Colorize;
 mod runtime;
 mod analysis;
-use runtime::Runtime;
+mod error;
+mod evaluator;
+mod sqlite;
+mod memory;
+pub use runtime::{Runtime, Options, Writeback, Format, Backend, Journal, Diff, Strategy, Stats, PredicateStats, Profile, StatementProfile, Statement, Derivation, QueryPlan};
+pub use error::RuntimeError;
 
-pub fn run(source_path: &str, verbose: bool) {
-    let runtime = Runtime::new(source_path, verbose);
-    match runtime {
-        Ok(runtime) => {
-            let _result = runtime.eval();
-        },
-        Err(error) => {
-            println!("{}: {}", "ERROR".red(), error);
+/// evaluate a source on the database at `db_path`, or on the database beside the source by default
+pub fn run(source_path: &str, db_path: Option<&str>, options: Options) {
+    let explain = options.explain;
+    let runtime = match db_path {
+        Some(db_path) => Runtime::with_db(source_path, db_path, options),
+        None => Runtime::new(source_path, options),
+    };
+    let result = runtime.and_then(|runtime| {
+        if explain {
+            runtime.explain().map(|sql| println!("{}", sql))
+        } else {
+            runtime.eval().map(|_| ())
         }
+    });
+    if let Err(error) = result {
+        println!("{}: {}", "ERROR".red(), error);
     }
-}
\ No newline at end of file
+}
+
+/// check a source and the database at `db_path`, or the database beside the source by default,
+/// printing every problem found without evaluating the program, and return whether none was found
+pub fn check(source_path: &str, db_path: Option<&str>, options: Options) -> bool {
+    let db_path = db_path.map_or_else(
+        || std::path::Path::new(source_path).with_extension("db").display().to_string(),
+        str::to_string
+    );
+    let errors = Runtime::check(source_path, &db_path, &options);
+    for error in errors.iter() {
+        println!("{}: {}", "ERROR".red(), error);
+    }
+    if errors.is_empty() {
+        println!("{}: {} is ready to be evaluated", "CHECK".green(), source_path);
+    }
+    errors.is_empty()
+}
+
+/// write the predicate dependency graph of a source to a Graphviz DOT file
+pub fn dump_graph(source_path: &str, graph_path: &str) {
+    let result = parse_file(std::path::Path::new(source_path))
+        .map_err(RuntimeError::from)
+        .and_then(|context| {
+            std::fs::write(graph_path, context.stratum.to_dot()).map_err(RuntimeError::from)
+        });
+    if let Err(error) = result {
+        println!("{}: {}", "ERROR".red(), error);
+    }
+}
+
+/// print the strata of a source in evaluation order, with the predicates of each stratum
+pub fn show_strata(source_path: &str) {
+    match parse_file(std::path::Path::new(source_path)) {
+        Ok(context) => {
+            println!("{}", "STRATA".green());
+            println!("{}", context.stratum.to_listing());
+        }
+        Err(error) => println!("{}: {}", "ERROR".red(), RuntimeError::from(error)),
+    }
+}
@@ -1,544 +1,2177 @@
 use super::context::Context;
+use super::magic;
 use super::ast::*;
 use super::analysis::*;
-use super::parse;
-use core::panic;
-use rusqlite::{Connection, params, Result, backup::Backup};
+use super::{parse_file, parse_atom};
+use super::error::RuntimeError;
+use super::evaluator::{Evaluator, Mode, Profiled, ScratchRelations, RESERVED_PREFIXES, delta_relation, temp_relation};
+use super::sqlite::{self, SqliteEvaluator};
+use super::memory::MemoryEvaluator;
+use rusqlite::{Connection, OpenFlags, OptionalExtension, Row, params, params_from_iter, backup::Backup, types::{Value, ValueRef}};
 use colored::Colorize;
-use std::error::Error;
+use log::{debug, log_enabled, trace, Level};
 use std::time::Duration;
-use std::collections::HashSet;
-use std::collections::HashMap;
+use std::cell::Cell;
+use std::path::{Path, PathBuf};
+use std::collections::{HashMap, HashSet};
+use ordered_float::{FloatIsNan, NotNan};
+
+/// [`Writeback`] decides what is written back to the source database after evaluation.
+#[derive(Clone, Copy, Debug)]
+pub enum Writeback {
+    /// the whole database, including every materialized idb
+    All,
+    /// only the tables of @output predicates
+    Outputs,
+    /// every materialized idb as the table `idb_predicate`,
+    /// so that the tables of edbs are never replaced
+    Idbs,
+    /// nothing, the source database is left untouched
+    Disabled,
+}
+
+/// [`Format`] decides how the results of queries are written.
+#[derive(Clone, Copy, Debug)]
+pub enum Format {
+    /// a preview of every query, with the first and last [`Options::preview`] entities printed, and csv files
+    Preview,
+    /// every query as a json array of objects keyed by column, with typed values
+    Json,
+}
+
+/// [`Backend`] decides which [`Evaluator`] derives the idbs.
+#[derive(Clone, Copy, Debug)]
+pub enum Backend {
+    /// every rule is compiled into SQL and evaluated by SQLite
+    Sqlite,
+    /// rules are evaluated on tuples held in memory, without arithmetic or aggregates.
+    /// the derived idbs are stored in the database afterwards
+    Memory,
+}
+
+/// [`Journal`] decides the rollback journal of the in-memory database evaluating the program.
+#[derive(Clone, Copy, Debug)]
+pub enum Journal {
+    /// the journal is held in memory, so that a failed statement is rolled back
+    Memory,
+    /// no journal is kept, which is faster but leaves a failed statement half applied
+    Off,
+}
+
+/// [`Diff`] decides how SQLite keeps the tuples newly derived by an iteration,
+/// i.e. those missing from the relation. both give the same delta.
+#[derive(Clone, Copy, Debug)]
+pub enum Diff {
+    /// a left join of the new tuples with the relation on every column, keeping the unmatched ones
+    AntiJoin,
+    /// the new tuples `EXCEPT` those of the relation
+    Except,
+}
+
+/// [`Strategy`] decides what an iteration of a recursive rule derives from.
+/// both reach the same fixpoint, after as many iterations.
+#[derive(Clone, Copy, Debug)]
+pub enum Strategy {
+    /// each recursive atom in turn reads the tuples newly derived by the previous iteration
+    SemiNaive,
+    /// every atom reads the full relation, so that each iteration derives every tuple again
+    Naive,
+}
+
+/// [`Options`] configures how a [`Runtime`] evaluates a program.
+#[derive(Clone, Debug)]
+pub struct Options {
+    pub writeback: Writeback,
+    /// abort a recursive predicate which has not reached a fixpoint after this many iterations
+    pub max_iterations: Option<usize>,
+    /// print the statements of [`Runtime::explain`] rather than evaluating the program
+    pub explain: bool,
+    pub format: Format,
+    /// the number of tuples printed from the head and from the tail of a query which has more than twice as many,
+    /// every tuple is printed otherwise, and none if it is 0
+    pub preview: usize,
+    /// rewrite the program with magic sets, so that queries with constants only derive relevant tuples
+    pub magic: bool,
+    pub backend: Backend,
+    /// print the [`Stats`] of the evaluation after writing the queries
+    pub stats: bool,
+    /// print the [`Profile`] of the statements of the evaluation after writing the queries
+    pub profile_sql: bool,
+    /// print the plans of [`Runtime::explain_plan`] after writing the queries
+    pub query_plan: bool,
+    /// evaluate the independent strata of a rank in parallel, each on its own in-memory database,
+    /// unless the program is traced or evaluated by the memory backend
+    pub parallel: bool,
+    /// only evaluate and write this @output predicate, and the idbs it depends on
+    pub query: Option<String>,
+    /// print a [`Derivation`] of the tuple written as `predicate,value,...` after writing the queries
+    pub trace: Option<String>,
+    /// print the tuples matching the atom, e.g. `reachable(a, Y)`, after writing the queries
+    pub ask: Option<String>,
+    /// print and return the tuples of queries ordered by their columns, rather than as SQLite returns them
+    pub sorted: bool,
+    /// print and return at most this number of tuples of each query, the idbs are still derived in full
+    pub limit: Option<usize>,
+    /// floats compared with `==` are equal if they differ by less than this tolerance, rather than exactly
+    pub epsilon: Option<NotNan<f64>>,
+    /// warn about every edb holding a tuple more than once after loading the edbs
+    pub warn_duplicate_edb: bool,
+    /// the page cache of the in-memory database in megabytes, SQLite's default if none
+    pub cache_mb: Option<usize>,
+    pub journal: Journal,
+    pub diff: Diff,
+    pub strategy: Strategy,
+    /// idbs too large to hold in memory, whose tables are stored in an on-disk database
+    /// beside the database of the edbs, with the extension .disk.db.
+    /// their delta and temp relations are still held in memory
+    pub disk: Vec<String>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            writeback: Writeback::All,
+            max_iterations: None,
+            explain: false,
+            format: Format::Preview,
+            preview: 10,
+            magic: false,
+            backend: Backend::Sqlite,
+            stats: false,
+            profile_sql: false,
+            query_plan: false,
+            parallel: false,
+            query: None,
+            trace: None,
+            ask: None,
+            sorted: false,
+            limit: None,
+            epsilon: None,
+            warn_duplicate_edb: false,
+            cache_mb: None,
+            journal: Journal::Memory,
+            diff: Diff::AntiJoin,
+            strategy: Strategy::SemiNaive,
+            disk: Vec::new(),
+        }
+    }
+}
+
+/// [`Stats`] records the fixpoint iterations of every recursive predicate,
+/// in the order they are evaluated.
+#[derive(Clone, Debug, Default)]
+pub struct Stats {
+    pub predicates: Vec<PredicateStats>,
+    pub profile: Profile,
+}
+
+/// the iterations of the recursive rules of a predicate,
+/// with the number of tuples in the delta after each iteration.
+/// the last iteration of each rule reaches the fixpoint with an empty delta.
+#[derive(Clone, Debug)]
+pub struct PredicateStats {
+    pub predicate: String,
+    pub deltas: Vec<usize>,
+}
+
+impl PredicateStats {
+    pub fn iterations(&self) -> usize {
+        self.deltas.len()
+    }
+}
+
+impl Stats {
+    pub fn get(&self, predicate: &str) -> Option<&PredicateStats> {
+        self.predicates.iter().find(|stats| stats.predicate == predicate)
+    }
+
+    /// add the iterations of the predicates evaluated separately, e.g. by another thread
+    fn extend(&mut self, other: Stats) {
+        self.predicates.extend(other.predicates);
+        for profile in other.profile.statements {
+            self.profile.add(profile.statement, profile.executions, profile.elapsed);
+        }
+    }
+
+    fn record(&mut self, predicate: &str, delta: usize) {
+        match self.predicates.iter_mut().find(|stats| stats.predicate == predicate) {
+            Some(stats) => stats.deltas.push(delta),
+            None => self.predicates.push(PredicateStats {
+                predicate: predicate.to_string(),
+                deltas: vec![delta],
+            }),
+        }
+    }
+
+    /// print a table of the iterations and delta sizes of every recursive predicate
+    pub fn print(&self) {
+        let width = self.predicates.iter()
+            .map(|stats| stats.predicate.len())
+            .chain(std::iter::once("predicate".len()))
+            .max()
+            .unwrap_or(0);
+        println!("{}", "STATS".green());
+        println!("{:width$}  {:>10}  deltas", "predicate", "iterations", width = width);
+        for stats in self.predicates.iter() {
+            let deltas = stats.deltas.iter()
+                .map(|delta| delta.to_string())
+                .collect::<Vec<String>>();
+            println!("{:width$}  {:>10}  {}", stats.predicate, stats.iterations(), deltas.join(", "), width = width);
+        }
+    }
+}
+
+/// [`Statement`] is the kind of an operation performed by the [`Evaluator`] during an evaluation,
+/// a statement of SQLite with the sqlite backend.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Statement {
+    /// creating, copying or dropping a relation, or creating the indexes of a recursive rule
+    Create,
+    /// deriving the tuples of a rule from the full relations of its body
+    BaseInsert,
+    /// deriving the tuples of a recursive rule from the delta relation of one atom
+    DeltaJoin,
+    /// keeping the newly derived tuples missing from the relation
+    Diff,
+    /// inserting the delta into the relation
+    Merge,
+    /// counting the tuples of the delta
+    Count,
+    /// beginning, committing or rolling back an iteration
+    Transaction,
+}
+
+impl std::fmt::Display for Statement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Statement::Create => "create",
+            Statement::BaseInsert => "base-insert",
+            Statement::DeltaJoin => "delta-join",
+            Statement::Diff => "diff",
+            Statement::Merge => "merge",
+            Statement::Count => "count",
+            Statement::Transaction => "transaction",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// [`Profile`] records the wall-clock time spent by each kind of [`Statement`],
+/// in the order they are first executed.
+#[derive(Clone, Debug, Default)]
+pub struct Profile {
+    pub statements: Vec<StatementProfile>,
+}
+
+/// the number of statements of a kind, and the time they took altogether
+#[derive(Clone, Debug)]
+pub struct StatementProfile {
+    pub statement: Statement,
+    pub executions: usize,
+    pub elapsed: Duration,
+}
+
+impl Profile {
+    pub fn get(&self, statement: Statement) -> Option<&StatementProfile> {
+        self.statements.iter().find(|profile| profile.statement == statement)
+    }
+
+    pub(crate) fn record(&mut self, statement: Statement, elapsed: Duration) {
+        self.add(statement, 1, elapsed);
+    }
+
+    fn add(&mut self, statement: Statement, executions: usize, elapsed: Duration) {
+        match self.statements.iter_mut().find(|profile| profile.statement == statement) {
+            Some(profile) => {
+                profile.executions += executions;
+                profile.elapsed += elapsed;
+            }
+            None => self.statements.push(StatementProfile { statement, executions, elapsed }),
+        }
+    }
+
+    /// print a table of the executions and cumulative time of every kind of statement
+    pub fn print(&self) {
+        let width = self.statements.iter()
+            .map(|profile| profile.statement.to_string().len())
+            .chain(std::iter::once("statement".len()))
+            .max()
+            .unwrap_or(0);
+        println!("{}", "PROFILE".green());
+        println!("{:width$}  {:>10}  elapsed", "statement", "executions", width = width);
+        for profile in self.statements.iter() {
+            println!("{:width$}  {:>10}  {:.3}ms",
+                profile.statement.to_string(),
+                profile.executions,
+                profile.elapsed.as_secs_f64() * 1000.0,
+                width = width
+            );
+        }
+    }
+}
+
+/// [`Derivation`] explains why a tuple holds.
+/// a tuple of an edb is given, and a tuple of an idb is derived by one of its rules
+/// from the tuples matching the non-negated atoms in the body of the rule.
+#[derive(Clone, Debug)]
+pub struct Derivation {
+    pub predicate: String,
+    pub tuple: Tuple,
+    /// the index of the deriving rule among the rules of the predicate, none for an edb
+    pub rule: Option<usize>,
+    pub premises: Vec<Derivation>,
+}
+
+impl Derivation {
+    fn fmt_indented(&self, f: &mut std::fmt::Formatter<'_>, depth: usize) -> std::fmt::Result {
+        let values = self.tuple.iter().map(format_value).collect::<Vec<String>>();
+        write!(f, "{}{}({})", "  ".repeat(depth), self.predicate, values.join(", "))?;
+        match self.rule {
+            // rules are numbered from 1 in the order they are written
+            Some(index) => writeln!(f, " by rule {}", index + 1)?,
+            None => writeln!(f)?,
+        }
+        for premise in self.premises.iter() {
+            premise.fmt_indented(f, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Derivation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
+
+/// [`QueryPlan`] is the plan SQLite chooses for a statement of an iteration of a recursive rule,
+/// one statement of each occurrence of the head in the body under semi-naive evaluation.
+#[derive(Clone, Debug)]
+pub struct QueryPlan {
+    pub predicate: String,
+    /// the index of the rule among the rules of the predicate
+    pub rule: usize,
+    pub sql: String,
+    /// the rows of `EXPLAIN QUERY PLAN`, each indented by its depth in the plan
+    pub plan: Vec<String>,
+}
+
+impl std::fmt::Display for QueryPlan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // rules are numbered from 1 in the order they are written
+        writeln!(f, "{} by rule {}", self.predicate, self.rule + 1)?;
+        for row in self.plan.iter() {
+            writeln!(f, "  {}", row)?;
+        }
+        Ok(())
+    }
+}
 
 pub struct Runtime {
-    source_db: String,
-    verbose: bool,
+    /// the database read and written back, none for a runtime built by [`Runtime::from_facts`]
+    source_db: Option<String>,
+    source_dir: PathBuf,
+    options: Options,
     context: Context,
     analyzer: Analyzer,
-    database: Connection
+    database: Connection,
+    /// the number of times tuples have been recorded under the trace option
+    step: Cell<usize>,
+    /// the edbs changed by [`Runtime::add_facts`] since the last evaluation
+    changed: HashSet<String>,
 }
 
 impl Runtime {
-    pub fn new(source_path: &str, verbose: bool) -> Result<Self, Box<dyn Error>> {
-        let context = parse(source_path);
-        // database name is the same as source name, but replace postfix .amo with .db
-        let mut parts = source_path.rsplitn(2, '.').collect::<Vec<&str>>();
-        if let Some(index) = parts.iter_mut()
-            .position(|&mut part| part == "amo") {
-                parts[index] = "db";
-        }
-        let source_db = parts.into_iter()
-            .rev()
-            .collect::<Vec<&str>>()
-            .join(".");
-        if verbose {
-            println!("{}: {}", "LOADING".green(), source_db);
-        }
-        let database_disk = Connection::open(source_db.clone())?;
+    /// build a runtime on the database beside the source, named as the source with the extension .db,
+    /// e.g. example/closure/closure.db for example/closure/closure.amo
+    pub fn new(source_path: &str, options: Options) -> Result<Self, RuntimeError> {
+        let source_db = Path::new(source_path).with_extension("db");
+        Self::with_db(source_path, &source_db.display().to_string(), options)
+    }
+
+    /// build a runtime on the database at `db_path`, wherever the source is.
+    /// csv and json files of @input(file) and @output(file) are still looked up in the directory of the source
+    pub fn with_db(source_path: &str, db_path: &str, options: Options) -> Result<Self, RuntimeError> {
+        let context = parse_file(Path::new(source_path))?;
+        // type errors are reported before the database is touched
+        let (context, analyzer) = analyze(context, &options)?;
+        let source_db = db_path.to_string();
+        debug!("LOADING: {}", source_db);
+        // a missing database is read as an empty one rather than created,
+        // so a program with inline facts runs without a database file
+        let database_disk = if Path::new(&source_db).exists() {
+            Connection::open(source_db.clone())?
+        } else {
+            Connection::open_in_memory()?
+        };
         let mut database = Connection::open_in_memory()?;
-        // check if all edbs are present in database
-        for (table, rule) in context.edbs.iter() {
-            let sql = format!("SELECT name FROM sqlite_master WHERE type='table' AND name='{}';", table);
-            let mut stmt = database_disk.prepare(&sql)?;
-            let mut rows = stmt.query(params![])?;
-            let rows_exist = rows.next()?;
-            if rows_exist.is_none() {
-                panic!("EDB {} is not present in database", table);
-            }
-            let arity = rule.head.terms.len();
-            // check if ebd table has the same arity as in the rule
-            let count_column = format!("PRAGMA table_info({})", table);
-            let mut count_stmt = database_disk.prepare(&count_column)?;
-            let count_rows = count_stmt.query_map(params![], |row| {
-                let name: String = row.get(1)?;
-                Ok(name)
-            })?;
-            let actual_arity = count_rows.count();
-            assert_eq!(arity, actual_arity);
+        // csv and json files are looked up in the directory of the source
+        let source_dir = Path::new(source_path).parent()
+            .unwrap_or(Path::new(""))
+            .to_path_buf();
+        if let Some(error) = check_edbs(&context, &database_disk, &source_dir)?.into_iter().next() {
+            return Err(error);
         }
         // clone database to memory
         {
             let backup = Backup::new(&database_disk, &mut database)?;
             backup.run_to_completion(5, Duration::from_millis(1), None)?;
         }
-        database_disk.close().unwrap();
-        let mut analyzer = Analyzer::new();
-        analyzer.type_inference(&context);
-        Ok(Self {
-            source_db,
-            verbose,
+        database_disk.close().map_err(|(_, error)| error)?;
+        let runtime = Self {
+            source_db: Some(source_db),
+            source_dir,
+            options,
+            context,
+            analyzer,
+            database,
+            step: Cell::new(0),
+            changed: HashSet::new(),
+        };
+        runtime.configure()?;
+        for (table, rule) in runtime.context.edbs.iter() {
+            if let IO::Read(None) = &rule.io {
+                runtime.coerce_edb(table)?;
+            }
+            if let IO::Read(Some(file)) = &rule.io {
+                let path = input_path(&runtime.source_dir, file);
+                match path.extension().and_then(|extension| extension.to_str()) {
+                    Some("json") => runtime.load_json_edb(table, &path)?,
+                    _ => runtime.load_csv_edb(table, &path)?,
+                }
+            }
+        }
+        for (table, facts) in runtime.context.facts.iter() {
+            runtime.load_facts(table, facts)?;
+        }
+        if runtime.options.warn_duplicate_edb {
+            runtime.warn_duplicate_edbs()?;
+        }
+        Ok(runtime)
+    }
+
+    /// every problem found in a program and its database at `db_path`, without evaluating the program:
+    /// a malformed program, an unknown column type or query, and every edb missing from the database,
+    /// declared with another arity or whose csv or json file is missing.
+    /// the database is only read, and an empty list means the program is ready to be evaluated
+    pub fn check(source_path: &str, db_path: &str, options: &Options) -> Vec<RuntimeError> {
+        let context = match parse_file(Path::new(source_path)) {
+            Ok(context) => context,
+            Err(error) => return vec![error.into()],
+        };
+        let mut errors = Vec::new();
+        if let Err(error) = analyze(context.clone(), options) {
+            errors.push(error);
+        }
+        let source_dir = Path::new(source_path).parent().unwrap_or(Path::new(""));
+        let database_disk = if Path::new(db_path).exists() {
+            Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        } else {
+            Connection::open_in_memory()
+        };
+        match database_disk.map_err(RuntimeError::from).and_then(|database| check_edbs(&context, &database, source_dir)) {
+            Ok(edb_errors) => errors.extend(edb_errors),
+            Err(error) => errors.push(error),
+        }
+        errors
+    }
+
+    /// build a runtime on an in-memory database, whose edbs hold the given tuples
+    /// rather than being read from a database file.
+    /// facts written in the source are inserted as well, and nothing is written back.
+    ///
+    /// ```
+    /// use amoeba::engine::{Runtime, Options};
+    /// use amoeba::syntax::{parse, ast::Constant};
+    /// use std::collections::HashMap;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let context = parse("
+    ///     @input
+    ///     edge(int, int)
+    ///     reachable(X, Y) :- edge(X, Y)
+    ///     reachable(X, Z) :- edge(X, Y), reachable(Y, Z)
+    ///     @output
+    ///     reachable(X, Y)
+    /// ")?;
+    /// // a chain 0 -> 1 -> 2 -> 3
+    /// let edges = (0..3).map(|i| vec![Constant::Integer(i), Constant::Integer(i + 1)]).collect();
+    /// let facts = HashMap::from([("edge".to_string(), edges)]);
+    /// let runtime = Runtime::from_facts(context, facts, Options::default())?;
+    /// runtime.materialize()?;
+    /// let reachable = &runtime.run_queries()?["reachable"];
+    /// assert_eq!(reachable.len(), 6);
+    /// assert!(reachable.contains(&vec![Constant::Integer(0), Constant::Integer(3)]));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_facts(
+        context: Context,
+        facts: HashMap<String, Vec<Tuple>>,
+        options: Options
+    ) -> Result<Self, RuntimeError> {
+        if !options.disk.is_empty() {
+            return Err(RuntimeError::DiskWithoutDatabase);
+        }
+        let (context, analyzer) = analyze(context, &options)?;
+        if let Some(table) = facts.keys().find(|table| !context.edbs.contains_key(*table)) {
+            return Err(RuntimeError::UnknownEdb(table.clone()));
+        }
+        let runtime = Self {
+            source_db: None,
+            source_dir: PathBuf::new(),
+            options,
             context,
             analyzer,
-            database
-        })
+            database: Connection::open_in_memory()?,
+            step: Cell::new(0),
+            changed: HashSet::new(),
+        };
+        runtime.configure()?;
+        for table in runtime.context.edbs.keys() {
+            let tuples = facts.get(table).map_or(&[][..], Vec::as_slice);
+            let arity = runtime.analyzer.data_types[table].len();
+            if let Some(tuple) = tuples.iter().find(|tuple| tuple.len() != arity) {
+                return Err(RuntimeError::ArityMismatch {
+                    table: table.clone(),
+                    expected: arity,
+                    actual: tuple.len(),
+                });
+            }
+            runtime.insert_tuples(table, tuples)?;
+        }
+        for (table, facts) in runtime.context.facts.iter() {
+            runtime.load_facts(table, facts)?;
+        }
+        if runtime.options.warn_duplicate_edb {
+            runtime.warn_duplicate_edbs()?;
+        }
+        Ok(runtime)
+    }
+
+    /// apply the pragmas of the options to the in-memory database evaluating the program,
+    /// temporary tables are always kept in memory rather than in temporary files.
+    /// the on-disk database of disk-backed idbs is attached as `disk`, and their tables are derived afresh
+    fn configure(&self) -> Result<(), RuntimeError> {
+        if let (Some(source_db), false) = (&self.source_db, self.options.disk.is_empty()) {
+            let disk_db = Path::new(source_db).with_extension("disk.db");
+            debug!("ATTACHING: {}", disk_db.display());
+            self.database.execute("ATTACH DATABASE ?1 AS disk", params![disk_db.display().to_string()])?;
+            for name in self.options.disk.iter() {
+                // a table written back by an earlier run would shadow the table on disk
+                self.execute(&format!("DROP TABLE IF EXISTS main.{}", name))?;
+                self.execute(&format!("DROP TABLE IF EXISTS disk.{}", name))?;
+            }
+        }
+        self.database.pragma_update(None, "temp_store", "MEMORY")?;
+        if let Some(cache_mb) = self.options.cache_mb {
+            // a negative cache size is a number of KiB rather than of pages
+            self.database.pragma_update(None, "cache_size", -(cache_mb as i64) * 1024)?;
+        }
+        let journal = match self.options.journal {
+            Journal::Memory => "MEMORY",
+            Journal::Off => "OFF",
+        };
+        self.database.pragma_update_and_check(None, "journal_mode", journal, |_| Ok(()))?;
+        if log_enabled!(Level::Debug) {
+            for pragma in ["temp_store", "cache_size", "journal_mode"] {
+                let value = self.database.pragma_query_value(None, pragma, |row| Ok(match row.get_ref(0)? {
+                    ValueRef::Integer(value) => value.to_string(),
+                    value => String::from_utf8_lossy(value.as_bytes()?).to_string(),
+                }))?;
+                debug!("PRAGMA: {} = {}", pragma, value);
+            }
+        }
+        Ok(())
     }
 
-    pub fn eval(&self) -> Result<(), Box<dyn Error>> {
+    /// insert the facts written in the source into the table of their edb,
+    /// alongside any tuples already loaded from the database or a csv file
+    fn load_facts(&self, table: &str, facts: &[Atom]) -> Result<(), RuntimeError> {
+        let tuples = facts.iter().map(|fact| {
+            fact.terms.iter().map(|term| match term {
+                Term::Constant(constant) => constant.clone(),
+                Term::Variable(_) => unreachable!("Fact should be ground"),
+            }).collect()
+        }).collect::<Vec<Tuple>>();
+        self.insert_tuples(table, &tuples)
+    }
+
+    /// insert tuples into the table of an edb, creating it if not present
+    fn insert_tuples(&self, table: &str, tuples: &[Tuple]) -> Result<(), RuntimeError> {
+        let type_info = self.analyzer.data_types.get(table)
+            .expect("EDB should be present in type info");
+        let columns = type_info.iter()
+            .enumerate()
+            .map(|(i, data_type)| format!("column_{} {}", i, data_type.sql_type()))
+            .collect::<Vec<String>>();
+        let create_sql = format!("CREATE TABLE IF NOT EXISTS {} ({})", table, columns.join(", "));
+        self.execute(&create_sql)?;
+        self.bulk_insert(table, tuples)
+    }
+
+    /// insert tuples into a table by a single prepared `INSERT OR IGNORE` within one transaction,
+    /// each value is bound as typed by the column types of the table
+    fn bulk_insert(&self, table: &str, tuples: &[Tuple]) -> Result<(), RuntimeError> {
+        let type_info = self.analyzer.data_types.get(table)
+            .expect("Table should be present in type info");
+        let placeholders = (1..=type_info.len())
+            .map(|i| format!("?{}", i))
+            .collect::<Vec<String>>();
+        let insert_sql = format!("INSERT OR IGNORE INTO {} VALUES ({})", table, placeholders.join(", "));
+        trace!("EXECUTE: {} ({} tuples)", insert_sql, tuples.len());
+        let transaction = self.database.unchecked_transaction()?;
+        {
+            let mut stmt = transaction.prepare(&insert_sql)?;
+            for tuple in tuples {
+                let values = tuple.iter()
+                    .zip(type_info.iter())
+                    .map(|(constant, data_type)| constant_to_value(constant, data_type));
+                stmt.execute(params_from_iter(values))?;
+            }
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// create the table of an edb and load its tuples from a csv file.
+    /// each field is parsed by the declared column type,
+    /// the first row is skipped as a header if it is `column_0, column_1, ...` or the declared column names,
+    /// or if it does not parse under the declared types.
+    /// an edb whose columns are all named requires the header, so that a csv written by the @output
+    /// of another program is only read if its columns are the declared ones
+    fn load_csv_edb(&self, table: &str, path: &Path) -> Result<(), RuntimeError> {
+        debug!("LOADING: {}", path.display());
+        let file = path.display().to_string();
+        let type_info = self.create_edb_table(table)?;
+        let columns = self.analyzer.columns(table);
+        let named = self.analyzer.column_names.get(table)
+            .is_some_and(|names| names.iter().all(Option::is_some));
+        let mut tuples = Vec::new();
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .trim(csv::Trim::All)
+            .from_path(path)
+            .map_err(|error| RuntimeError::Csv { file: file.clone(), error })?;
+        for (index, record) in reader.records().enumerate() {
+            let record = record.map_err(|error| RuntimeError::Csv { file: file.clone(), error })?;
+            let line = record.position().map_or(index as u64 + 1, |position| position.line());
+            if record.len() != type_info.len() {
+                return Err(RuntimeError::CsvRow {
+                    file,
+                    line,
+                    reason: format!("expected {} fields, found {}", type_info.len(), record.len()),
+                });
+            }
+            if index == 0 {
+                let is_header = record.iter()
+                    .enumerate()
+                    .all(|(i, field)| field == format!("column_{}", i))
+                    || record.iter().eq(columns.iter());
+                if is_header {
+                    continue;
+                }
+                if named {
+                    return Err(RuntimeError::CsvRow {
+                        file,
+                        line,
+                        reason: format!("the header `{}` does not match the declared columns `{}`",
+                            record.iter().collect::<Vec<&str>>().join(", "),
+                            columns.join(", ")
+                        ),
+                    });
+                }
+            }
+            let tuple = record.iter()
+                .zip(type_info.iter())
+                .map(|(field, data_type)| {
+                    data_type.parse_value(field)
+                        .and_then(|value| value_to_constant(value, data_type).ok())
+                        .ok_or((field, data_type))
+                })
+                .collect::<Result<Tuple, _>>();
+            let tuple = match tuple {
+                Ok(tuple) => tuple,
+                Err(_) if index == 0 => continue,
+                Err((field, data_type)) => {
+                    return Err(RuntimeError::CsvRow {
+                        file,
+                        line,
+                        reason: format!("`{}` is not a valid {:?}", field, data_type),
+                    });
+                }
+            };
+            tuples.push(tuple);
+        }
+        self.bulk_insert(table, &tuples)
+    }
+
+    /// create the table of an edb and load its tuples from a json file, an array of records.
+    /// a record is an object keyed by the column names, `column_0, column_1, ...` or the declared ones,
+    /// or an array of values in the order of the columns.
+    /// a value is a number, a string or a boolean as the declared column type, an integer is also a float
+    fn load_json_edb(&self, table: &str, path: &Path) -> Result<(), RuntimeError> {
+        debug!("LOADING: {}", path.display());
+        let file = path.display().to_string();
+        let type_info = self.create_edb_table(table)?;
+        let invalid = |reason: String| RuntimeError::Json { file: file.clone(), reason };
+        let text = std::fs::read_to_string(path).map_err(|error| invalid(error.to_string()))?;
+        let json = serde_json::from_str::<serde_json::Value>(&text).map_err(|error| invalid(error.to_string()))?;
+        let serde_json::Value::Array(records) = json else {
+            return Err(invalid("expected an array of records".to_string()));
+        };
+        let columns = self.analyzer.columns(table);
+        let mut tuples = Vec::new();
+        for (index, record) in records.iter().enumerate() {
+            let invalid_record = |reason: String| RuntimeError::JsonRecord { file: file.clone(), record: index, reason };
+            let values = match record {
+                serde_json::Value::Object(object) => columns.iter()
+                    .map(|column| object.get(column).ok_or_else(|| invalid_record(format!("field `{}` is missing", column))))
+                    .collect::<Result<Vec<&serde_json::Value>, RuntimeError>>()?,
+                serde_json::Value::Array(values) if values.len() == type_info.len() => values.iter().collect(),
+                serde_json::Value::Array(values) => {
+                    return Err(invalid_record(format!("expected {} fields, found {}", type_info.len(), values.len())));
+                }
+                _ => return Err(invalid_record("expected an object or an array".to_string())),
+            };
+            let tuple = values.into_iter()
+                .zip(type_info.iter())
+                .zip(columns.iter())
+                .map(|((value, data_type), column)| {
+                    json_to_constant(value, data_type).ok_or_else(|| {
+                        invalid_record(format!("field `{}`: {} is not a valid {:?}", column, value, data_type))
+                    })
+                })
+                .collect::<Result<Tuple, RuntimeError>>()?;
+            tuples.push(tuple);
+        }
+        self.bulk_insert(table, &tuples)
+    }
+
+    /// warn about the duplicate tuples of every edb, in the order of their names.
+    /// a tuple is counted once for each copy beyond the first
+    fn warn_duplicate_edbs(&self) -> Result<(), RuntimeError> {
+        let mut edbs = self.context.edbs.keys().collect::<Vec<&String>>();
+        edbs.sort();
+        for table in edbs {
+            if !self.has_table(table)? {
+                continue;
+            }
+            let columns = (0..self.analyzer.data_types[table].len())
+                .map(|i| format!("column_{}", i))
+                .collect::<Vec<String>>();
+            let sql = format!("SELECT COALESCE(SUM(copies - 1), 0) FROM \
+                (SELECT COUNT(*) AS copies FROM {} GROUP BY {} HAVING COUNT(*) > 1)",
+                table,
+                columns.join(", ")
+            );
+            trace!("EXECUTE: {}", sql);
+            let duplicates: i64 = self.database.query_row(&sql, params![], |row| row.get(0))?;
+            if duplicates > 0 {
+                eprintln!("{}: EDB {} has {} duplicate tuples", "WARNING".yellow(), table, duplicates);
+            }
+        }
+        Ok(())
+    }
+
+    /// cast the values of the table of an edb read from the database to its declared column types,
+    /// e.g. an integer stored as text, which would not join with integers.
+    /// the table is only rewritten if a value is stored as another type, and fails on a value
+    /// which cannot be coerced, e.g. a text which is not a number in an int column
+    fn coerce_edb(&self, table: &str) -> Result<(), RuntimeError> {
+        if !self.has_table(table)? {
+            return Ok(());
+        }
+        let type_info = self.analyzer.data_types.get(table)
+            .expect("EDB should be present in type info");
+        let mismatches = type_info.iter()
+            .enumerate()
+            .map(|(i, data_type)| {
+                let classes = data_type.storage_classes().iter()
+                    .map(|class| format!("'{}'", class))
+                    .collect::<Vec<String>>();
+                format!("typeof(column_{}) NOT IN ({})", i, classes.join(", "))
+            })
+            .collect::<Vec<String>>();
+        let sql = format!("SELECT EXISTS (SELECT 1 FROM {} WHERE {})", table, mismatches.join(" OR "));
+        if !self.database.query_row(&sql, params![], |row| row.get::<_, bool>(0))? {
+            return Ok(());
+        }
+        debug!("COERCE: {}", table);
+        let mut tuples = Vec::new();
+        {
+            let mut stmt = self.database.prepare(&format!("SELECT * FROM {}", table))?;
+            let mut rows = stmt.query([])?;
+            while let Some(row) = rows.next()? {
+                let tuple = type_info.iter()
+                    .enumerate()
+                    .map(|(i, data_type)| {
+                        let value = row.get_ref(i)?;
+                        Ok(coerce_value(value, data_type).ok_or_else(|| RuntimeError::Coercion {
+                            table: table.to_string(),
+                            row: tuples.len() + 1,
+                            reason: format!("{} in column_{} is not a valid {:?}", format_sql_value(value), i, data_type),
+                        }))
+                    })
+                    .collect::<Result<Result<Tuple, RuntimeError>, rusqlite::Error>>()??;
+                tuples.push(tuple);
+            }
+        }
+        self.create_edb_table(table)?;
+        self.bulk_insert(table, &tuples)
+    }
+
+    /// replace the table of an edb by an empty one with its declared column types
+    fn create_edb_table(&self, table: &str) -> Result<&[DataType], RuntimeError> {
+        let type_info = self.analyzer.data_types.get(table)
+            .expect("EDB should be present in type info");
+        let columns = type_info.iter()
+            .enumerate()
+            .map(|(i, data_type)| format!("column_{} {}", i, data_type.sql_type()))
+            .collect::<Vec<String>>();
+        let sql = format!("DROP TABLE IF EXISTS {}; CREATE TABLE {} ({});", table, table, columns.join(", "));
+        trace!("EXECUTE: {}", sql);
+        self.database.execute_batch(&sql)?;
+        Ok(type_info)
+    }
+
+    pub fn eval(&self) -> Result<Stats, RuntimeError> {
+        // the traced tuple is checked before evaluating the program
+        let trace = self.options.trace.as_ref()
+            .map(|trace| self.parse_trace(trace))
+            .transpose()?;
+        let ask = self.options.ask.as_ref()
+            .map(|ask| self.parse_ask(ask))
+            .transpose()?;
+        let stats = self.materialize()?;
+        self.write_queries()?;
+        if let Some(source_db) = &self.source_db {
+            match self.options.writeback {
+                Writeback::All => {
+                    // write whole database to disk
+                    let mut database_disk = Connection::open(source_db)?;
+                    let backup = Backup::new(&self.database, &mut database_disk)?;
+                    backup.run_to_completion(5, Duration::from_millis(1), None)?;
+                }
+                Writeback::Outputs => {
+                    let tables = self.context.queries.keys()
+                        .map(|name| (name.clone(), name.clone()))
+                        .collect::<Vec<(String, String)>>();
+                    self.write_tables(source_db, &tables)?;
+                }
+                Writeback::Idbs => {
+                    let tables = self.context.ordered_idbs().into_iter()
+                        .map(|name| (name.clone(), format!("idb_{}", name)))
+                        .collect::<Vec<(String, String)>>();
+                    self.write_tables(source_db, &tables)?;
+                }
+                Writeback::Disabled => {}
+            }
+        }
+        if self.options.stats {
+            stats.print();
+        }
+        if self.options.profile_sql {
+            stats.profile.print();
+        }
+        if self.options.query_plan {
+            for plan in self.explain_plan()? {
+                print!("{}: {}", "PLAN".green(), plan);
+            }
+        }
+        if let Some((predicate, tuple)) = trace {
+            let values = tuple.iter().map(format_value).collect::<Vec<String>>();
+            println!("{}: {}({})", "TRACE".green(), predicate, values.join(", "));
+            match self.trace(&predicate, &tuple)? {
+                Some(derivation) => print!("{}", derivation),
+                None => println!("not derived"),
+            }
+        }
+        if let Some(rule) = ask {
+            println!("{}: {}", "ASK".green(), rule.head);
+            let entities = self.query(&rule)?;
+            entities.iter().for_each(|entity| {
+                println!("{}", entity.iter().map(format_value).collect::<Vec<String>>().join(", "));
+            });
+            println!("{}: {}", "COUNT".green(), entities.len());
+        }
+        Ok(stats)
+    }
+
+    /// copy each table `from` to the table `to` of the source database, replacing it,
+    /// and leaving every other table as it is. a table which is not derived is skipped
+    fn write_tables(&self, source_db: &str, tables: &[(String, String)]) -> Result<(), RuntimeError> {
+        // checked before attaching, so that a table only present in the source database is not found
+        let derived = tables.iter()
+            .map(|(from, _)| self.has_table(from))
+            .collect::<Result<Vec<bool>, RuntimeError>>()?;
+        self.database.execute("ATTACH DATABASE ?1 AS source", params![source_db])?;
+        for ((from, to), _) in tables.iter().zip(derived).filter(|(_, derived)| *derived) {
+            let sql = format!("DROP TABLE IF EXISTS source.{}; CREATE TABLE source.{} AS SELECT * FROM {};",
+                to,
+                to,
+                from
+            );
+            trace!("EXECUTE: {}", sql);
+            self.database.execute_batch(&sql)?;
+        }
+        self.database.execute("DETACH DATABASE source", params![])?;
+        Ok(())
+    }
+
+    /// evaluate every idb stratum by stratum, without writing any output,
+    /// and return the iterations of the recursive predicates.
+    /// derivations are recorded in the database, so a traced program is always evaluated by SQLite.
+    ///
+    /// the evaluation is logged by the [`log`] crate, the iterations of recursive predicates at the debug level
+    /// and every executed statement at the trace level, each record as `LABEL: message`.
+    ///
+    /// ```
+    /// use amoeba::engine::{Runtime, Options};
+    /// use amoeba::syntax::parse;
+    /// use log::{LevelFilter, Log, Metadata, Record};
+    /// use std::collections::HashMap;
+    /// use std::sync::Mutex;
+    ///
+    /// struct Capture(Mutex<Vec<String>>);
+    ///
+    /// impl Log for Capture {
+    ///     fn enabled(&self, _: &Metadata) -> bool {
+    ///         true
+    ///     }
+    ///
+    ///     fn log(&self, record: &Record) {
+    ///         self.0.lock().unwrap().push(format!("{} {}", record.level(), record.args()));
+    ///     }
+    ///
+    ///     fn flush(&self) {}
+    /// }
+    ///
+    /// static CAPTURE: Capture = Capture(Mutex::new(Vec::new()));
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// log::set_logger(&CAPTURE).expect("Logger should only be set once");
+    /// log::set_max_level(LevelFilter::Trace);
+    /// let context = parse("
+    ///     edge(a, b).
+    ///     edge(b, c).
+    ///     reachable(X, Y) :- edge(X, Y)
+    ///     reachable(X, Z) :- reachable(X, Y), edge(Y, Z)
+    /// ")?;
+    /// let runtime = Runtime::from_facts(context, HashMap::new(), Options::default())?;
+    /// runtime.materialize()?;
+    /// let records = CAPTURE.0.lock().unwrap();
+    /// assert!(records.iter().any(|record| record.starts_with("TRACE EXECUTE: INSERT OR IGNORE INTO reachable")));
+    /// assert!(records.contains(&"DEBUG ITERATE: reachable(0)".to_string()));
+    /// assert!(records.contains(&"TRACE COMMIT: reachable(0)".to_string()));
+    /// assert!(records.contains(&"DEBUG FIXPOINT: reachable(1)".to_string()));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn materialize(&self) -> Result<Stats, RuntimeError> {
+        self.materialize_idbs(&self.context.idbs.keys().cloned().collect())
+    }
+
+    /// insert tuples into the table of an edb, to be derived from by [`Runtime::reeval`].
+    ///
+    /// ```
+    /// use amoeba::engine::{Runtime, Options};
+    /// use amoeba::syntax::{parse, ast::Constant};
+    /// use std::collections::HashMap;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let context = parse("
+    ///     edge(a, b).
+    ///     edge(b, c).
+    ///     reachable(X, Y) :- edge(X, Y)
+    ///     reachable(X, Z) :- reachable(X, Y), edge(Y, Z)
+    ///     @output
+    ///     reachable(X, Y)
+    /// ")?;
+    /// let mut runtime = Runtime::from_facts(context, HashMap::new(), Options::default())?;
+    /// runtime.materialize()?;
+    /// assert_eq!(runtime.run_queries()?["reachable"].len(), 3);
+    /// // an edge c -> d makes d reachable from a, b and c
+    /// let edge = vec![Constant::Symbol("c".to_string()), Constant::Symbol("d".to_string())];
+    /// runtime.add_facts("edge", vec![edge])?;
+    /// runtime.reeval()?;
+    /// let reachable = &runtime.run_queries()?["reachable"];
+    /// assert_eq!(reachable.len(), 6);
+    /// assert!(reachable.contains(&vec![Constant::Symbol("a".to_string()), Constant::Symbol("d".to_string())]));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn add_facts(&mut self, predicate: &str, tuples: Vec<Tuple>) -> Result<(), RuntimeError> {
+        self.check_tuples(predicate, &tuples)?;
+        self.insert_tuples(predicate, &tuples)?;
+        self.changed.insert(predicate.to_string());
+        Ok(())
+    }
+
+    /// delete a tuple from the table of an edb, the idbs derived from it are derived afresh by [`Runtime::reeval`],
+    /// and return whether the tuple was present.
+    ///
+    /// ```
+    /// use amoeba::engine::{Runtime, Options};
+    /// use amoeba::syntax::{parse, ast::Constant};
+    /// use std::collections::HashMap;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let context = parse("
+    ///     edge(a, b).
+    ///     edge(b, c).
+    ///     edge(a, c).
+    ///     edge(c, d).
+    ///     reachable(X, Y) :- edge(X, Y)
+    ///     reachable(X, Z) :- reachable(X, Y), edge(Y, Z)
+    ///     @output
+    ///     reachable(X, Y)
+    /// ")?;
+    /// let mut runtime = Runtime::from_facts(context, HashMap::new(), Options::default())?;
+    /// runtime.materialize()?;
+    /// assert_eq!(runtime.run_queries()?["reachable"].len(), 6);
+    /// // c and d are still reachable from a through a -> c, but not from b without b -> c
+    /// let symbol = |name: &str| Constant::Symbol(name.to_string());
+    /// assert!(runtime.retract("edge", vec![symbol("b"), symbol("c")])?);
+    /// runtime.reeval()?;
+    /// let reachable = &runtime.run_queries()?["reachable"];
+    /// assert_eq!(reachable.len(), 4);
+    /// assert!(reachable.contains(&vec![symbol("a"), symbol("d")]));
+    /// assert!(!reachable.contains(&vec![symbol("b"), symbol("d")]));
+    /// // a tuple is typed by the columns of its edb
+    /// assert!(runtime.retract("edge", vec![symbol("a"), Constant::Integer(1)]).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn retract(&mut self, predicate: &str, tuple: Tuple) -> Result<bool, RuntimeError> {
+        self.check_tuples(predicate, std::slice::from_ref(&tuple))?;
+        let type_info = &self.analyzer.data_types[predicate];
+        let where_sql = (0..type_info.len())
+            .map(|i| format!("column_{} = ?{}", i, i + 1))
+            .collect::<Vec<String>>();
+        let delete_sql = format!("DELETE FROM {} WHERE {}", predicate, where_sql.join(" AND "));
+        trace!("EXECUTE: {}", delete_sql);
+        let values = tuple.iter()
+            .zip(type_info.iter())
+            .map(|(constant, data_type)| constant_to_value(constant, data_type));
+        let deleted = self.database.execute(&delete_sql, params_from_iter(values))?;
+        if deleted > 0 {
+            self.changed.insert(predicate.to_string());
+        }
+        Ok(deleted > 0)
+    }
+
+    /// tuples given for an edb have its arity, and each value the type of its column,
+    /// an integer is accepted in a float column
+    fn check_tuples(&self, predicate: &str, tuples: &[Tuple]) -> Result<(), RuntimeError> {
+        if !self.context.edbs.contains_key(predicate) {
+            return Err(RuntimeError::UnknownEdb(predicate.to_string()));
+        }
+        let type_info = &self.analyzer.data_types[predicate];
+        for tuple in tuples {
+            if tuple.len() != type_info.len() {
+                return Err(RuntimeError::ArityMismatch {
+                    table: predicate.to_string(),
+                    expected: type_info.len(),
+                    actual: tuple.len(),
+                });
+            }
+            for (position, (constant, expected)) in tuple.iter().zip(type_info.iter()).enumerate() {
+                let found = DataType::of(constant);
+                if !expected.accepts(&found) {
+                    return Err(RuntimeError::TupleType {
+                        predicate: predicate.to_string(),
+                        position,
+                        expected: expected.clone(),
+                        found,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// evaluate again, after [`Runtime::materialize`], every idb depending on an edb
+    /// changed by [`Runtime::add_facts`] or [`Runtime::retract`] since the last evaluation.
+    /// the tables of those idbs are dropped and derived from scratch, every other idb is kept.
+    pub fn reeval(&mut self) -> Result<Stats, RuntimeError> {
+        let changed = std::mem::take(&mut self.changed);
+        let affected = self.context.idbs.keys()
+            .filter(|idb| self.context.stratum.dependencies_of(idb).iter().any(|name| changed.contains(name)))
+            .cloned()
+            .collect::<HashSet<String>>();
+        for name in affected.iter() {
+            self.execute(&format!("DROP TABLE IF EXISTS {}", name))?;
+            if self.options.trace.is_some() {
+                self.execute(&format!("DROP TABLE IF EXISTS temp.trace_{}", name))?;
+            }
+        }
+        self.materialize_idbs(&affected)
+    }
+
+    /// evaluate the given idbs, any other idb they depend on is already derived
+    fn materialize_idbs(&self, idbs: &HashSet<String>) -> Result<Stats, RuntimeError> {
+        match self.options.backend {
+            Backend::Memory if self.options.trace.is_none() => {
+                let mut memory = MemoryEvaluator::new();
+                for name in self.context.edbs.keys() {
+                    memory.insert(name, self.read_relation(name)?);
+                }
+                for name in self.context.ordered_idbs().iter().filter(|name| !idbs.contains(*name)) {
+                    memory.insert(name, self.read_relation(name)?);
+                }
+                let stats = self.evaluate(&mut memory, idbs)?;
+                // derived idbs are stored in the database, to be queried and written back
+                for name in self.context.ordered_idbs().iter().filter(|name| idbs.contains(*name)) {
+                    self.store_relation(name, memory.tuples(name))?;
+                }
+                Ok(stats)
+            }
+            _ if self.options.parallel && self.options.trace.is_none() => self.evaluate_parallel(idbs),
+            _ => self.evaluate(&mut self.sqlite(), idbs),
+        }
+    }
+
+    /// evaluate the given idbs rank by rank of their strata, see [`crate::syntax::stratify::Stratum::ranks`].
+    /// the strata of a rank are evaluated by threads, each on a worker runtime holding the relations
+    /// the stratum depends on, and the derived idbs are stored in the database afterwards
+    fn evaluate_parallel(&self, idbs: &HashSet<String>) -> Result<Stats, RuntimeError> {
+        let mut stats = Stats::default();
+        for rank in self.context.stratum.ranks() {
+            let groups = rank.iter()
+                .map(|level| self.context.stratum.strata[*level].iter()
+                    .filter(|name| idbs.contains(*name))
+                    .cloned()
+                    .collect::<HashSet<String>>())
+                .filter(|group| !group.is_empty())
+                .collect::<Vec<HashSet<String>>>();
+            if groups.len() < 2 {
+                for group in groups {
+                    stats.extend(self.evaluate(&mut self.sqlite(), &group)?);
+                }
+                continue;
+            }
+            let workers = groups.into_iter()
+                .map(|group| Ok((self.worker(&group)?, group)))
+                .collect::<Result<Vec<(Runtime, HashSet<String>)>, RuntimeError>>()?;
+            if log_enabled!(Level::Debug) {
+                let mut names = workers.iter()
+                    .flat_map(|(_, group)| group.iter().cloned())
+                    .collect::<Vec<String>>();
+                names.sort();
+                debug!("PARALLEL: {}", names.join(", "));
+            }
+            let results = std::thread::scope(|scope| {
+                let handles = workers.into_iter().map(|(worker, group)| scope.spawn(move || {
+                    let stats = worker.evaluate(&mut worker.sqlite(), &group)?;
+                    let relations = group.into_iter()
+                        .map(|name| worker.read_relation(&name).map(|tuples| (name, tuples)))
+                        .collect::<Result<Vec<(String, Vec<Tuple>)>, RuntimeError>>()?;
+                    Ok((stats, relations))
+                })).collect::<Vec<_>>();
+                handles.into_iter()
+                    .map(|handle| handle.join().expect("Worker should not panic"))
+                    .collect::<Vec<Result<(Stats, Vec<(String, Vec<Tuple>)>), RuntimeError>>>()
+            });
+            for result in results {
+                let (worker_stats, relations) = result?;
+                stats.extend(worker_stats);
+                for (name, tuples) in relations {
+                    self.store_relation(&name, tuples)?;
+                }
+            }
+        }
+        Ok(stats)
+    }
+
+    /// a runtime on its own in-memory database, holding the relations of every predicate
+    /// the given idbs depend on, to evaluate them on another thread
+    fn worker(&self, idbs: &HashSet<String>) -> Result<Runtime, RuntimeError> {
+        let worker = Runtime {
+            source_db: None,
+            source_dir: self.source_dir.clone(),
+            options: Options { disk: Vec::new(), trace: None, ..self.options.clone() },
+            context: self.context.clone(),
+            analyzer: self.analyzer.clone(),
+            database: Connection::open_in_memory()?,
+            step: Cell::new(0),
+            changed: HashSet::new(),
+        };
+        worker.configure()?;
+        let mut dependencies = idbs.iter()
+            .flat_map(|idb| self.context.stratum.dependencies_of(idb))
+            .filter(|name| !idbs.contains(name))
+            .collect::<Vec<String>>();
+        dependencies.sort();
+        dependencies.dedup();
+        for name in dependencies {
+            if !self.has_table(&name)? {
+                continue;
+            }
+            worker.sqlite().create_relation(&name, &self.analyzer.data_types[&name])?;
+            worker.bulk_insert(&name, &self.read_relation(&name)?)?;
+        }
+        Ok(worker)
+    }
+
+    fn evaluate(&self, evaluator: &mut dyn Evaluator, idbs: &HashSet<String>) -> Result<Stats, RuntimeError> {
+        let mut stats = Stats::default();
+        // every operation is timed, the profile is only printed under the profile_sql option
+        let evaluator = &mut Profiled::new(evaluator);
         let mut previous = self.context.edbs
             .keys()
             .cloned()
             .collect::<Vec<String>>();
         let queue = self.context.ordered_idbs();
-        queue.iter().for_each(|name| {
+        for name in queue.iter() {
+            if !idbs.contains(name) {
+                previous.push(name.to_string());
+                continue;
+            }
             let rules = self.context.idbs.get(name)
                 .expect("IDB should be present in context");
             assert!(
                 rules.iter()
                 .all(|rule| rule.head.terms.len() == rules[0].head.terms.len())
             );
-            self.apply_rules(rules, &previous);
+            self.apply_rules(evaluator, rules, &previous, &mut stats)?;
             previous.push(name.to_string());
-        });
-        self.write_queries()?;
-        // write whole database to disk
-        let mut database_disk = Connection::open(self.source_db.clone())?;
-        {
-            let backup = Backup::new(&self.database, &mut database_disk)?;
-            backup.run_to_completion(5, Duration::from_millis(1), None)?;
         }
-        Ok(())
+        stats.profile = std::mem::take(&mut evaluator.profile);
+        Ok(stats)
     }
 
-    pub fn write_queries(&self) -> Result<(), Box<dyn Error>> {
-        let queries = &self.context.queries;
-        queries.iter().for_each(|(query, rules)| {
+    /// read the tuples of every `@output` predicate after [`Runtime::materialize`].
+    /// tuples are typed by the inferred column types and follow the order of head terms,
+    /// tuples of several queries on the same predicate are concatenated.
+    ///
+    /// ```no_run
+    /// use amoeba::engine::{Runtime, Options, Writeback};
+    /// use amoeba::syntax::ast::Constant;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let options = Options { writeback: Writeback::Disabled, ..Default::default() };
+    /// let runtime = Runtime::new("example/closure/closure.amo", options)?;
+    /// runtime.materialize()?;
+    /// let results = runtime.run_queries()?;
+    /// for tuple in &results["path"] {
+    ///     if let [Constant::Symbol(from), Constant::Symbol(to)] = &tuple[..] {
+    ///         println!("{} reaches {}", from, to);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn run_queries(&self) -> Result<HashMap<String, Vec<Tuple>>, RuntimeError> {
+        let mut results: HashMap<String, Vec<Tuple>> = HashMap::new();
+        for (query, rules) in self.context.queries.iter() {
             for rule in rules {
-                let sql = format!("SELECT name FROM sqlite_master WHERE type='table' AND name='{}';", query);
-                let mut stmt = self.database.prepare(&sql).unwrap();
-                let mut rows = stmt.query(params![]).unwrap();
-                let rows_exist = rows.next().unwrap();
-                if rows_exist.is_none() {
-                    panic!("Query {} is not present in database", query);
-                }
-                // let rule = self.context.queries.get(query)
-                //     .expect("Query should be present in context");
-                let mut sql = format!("SELECT * FROM {}", query);
-                let mut where_sql = Vec::new();
-                let var_dict = VarDict::new(rule);
-                // push constant terms to where clause
-                rule.head.terms.iter().enumerate().for_each(|(term_index, term)| {
-                    if let Term::Constant(constant) = term {
-                        let column = format!("column_{}", term_index);
-                        where_sql.push(format!("{} = {}", column, constant));
-                    }
-                });
-                // push inner where_sql stmt
-                var_dict.head_dict.iter().for_each(|(_, indexes)| {
-                    indexes.iter().skip(1).for_each(|index| {
-                        let column = format!("column_{}", index);
-                        where_sql.push(format!("column_0 = {}", column));
-                    });
-                });
-                if !where_sql.is_empty() {
-                    sql.push_str(" WHERE ");
-                    sql.push_str(where_sql.join(" AND ").as_str());
+                let mut entities = self.query(rule)?;
+                results.entry(query.clone())
+                    .or_default()
+                    .append(&mut entities);
+            }
+        }
+        Ok(results)
+    }
+
+    /// read the tuples of the `@output` predicate `name` after [`Runtime::materialize`],
+    /// each mapped by `f` into a value of the caller, e.g. a struct built from the typed accessors of [`Constant`].
+    /// tuples come in the order of [`Runtime::run_queries`].
+    ///
+    /// ```
+    /// use amoeba::engine::{Runtime, Options};
+    /// use amoeba::syntax::{parse, ast::Constant};
+    /// use std::collections::HashMap;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let context = parse("
+    ///     @input
+    ///     edge(int, int)
+    ///     reachable(X, Y) :- edge(X, Y)
+    ///     reachable(X, Z) :- edge(X, Y), reachable(Y, Z)
+    ///     @output
+    ///     reachable(X, Y)
+    /// ")?;
+    /// let edges = (0..3).map(|i| vec![Constant::Integer(i), Constant::Integer(i + 1)]).collect();
+    /// let facts = HashMap::from([("edge".to_string(), edges)]);
+    /// let runtime = Runtime::from_facts(context, facts, Options::default())?;
+    /// runtime.materialize()?;
+    /// let mut reachable = runtime.collect_query("reachable", |tuple| {
+    ///     let column = |i: usize| tuple[i].as_i64().unwrap() as i32;
+    ///     (column(0), column(1))
+    /// })?;
+    /// reachable.sort();
+    /// assert_eq!(reachable, vec![(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)]);
+    /// assert!(runtime.collect_query("edge", |tuple| tuple.len()).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn collect_query<T>(&self, name: &str, mut f: impl FnMut(&[Constant]) -> T) -> Result<Vec<T>, RuntimeError> {
+        let Some(rules) = self.context.queries.get(name) else {
+            let mut available = self.context.queries.keys().cloned().collect::<Vec<String>>();
+            available.sort();
+            return Err(RuntimeError::UnknownQuery { query: name.to_string(), available });
+        };
+        let mut results = Vec::new();
+        for rule in rules {
+            results.extend(self.query(rule)?.iter().map(|tuple| f(tuple)));
+        }
+        Ok(results)
+    }
+
+    /// a derivation of a tuple after [`Runtime::materialize`] with the trace option, none if the tuple does not hold.
+    /// a tuple of an idb is derived by its first rule matching tuples recorded at an earlier step,
+    /// so that the derivation of a tuple of a recursive predicate is finite.
+    /// a tuple of an aggregating rule is derived from the whole group, and has no premises.
+    pub fn trace(&self, predicate: &str, tuple: &Tuple) -> Result<Option<Derivation>, RuntimeError> {
+        let matches = tuple.iter()
+            .enumerate()
+            .map(|(i, value)| format!("column_{} = {}", i, value.to_sql()))
+            .collect::<Vec<String>>();
+        let mut derivation = Derivation {
+            predicate: predicate.to_string(),
+            tuple: tuple.clone(),
+            rule: None,
+            premises: Vec::new(),
+        };
+        if self.context.edbs.contains_key(predicate) {
+            let sql = format!("SELECT 1 FROM {} WHERE {}", predicate, matches.join(" AND "));
+            let exists = self.database.prepare(&sql)?.exists([])?;
+            return Ok(exists.then_some(derivation));
+        }
+        let Some(rules) = self.context.idbs.get(predicate) else {
+            return Ok(None);
+        };
+        let sql = format!("SELECT step FROM temp.trace_{} WHERE {}", predicate, matches.join(" AND "));
+        let step = self.database.query_row(&sql, [], |row| row.get::<_, i64>(0)).optional()?;
+        let Some(step) = step else {
+            return Ok(None);
+        };
+        for (index, rule) in rules.iter().enumerate().filter(|(_, rule)| !rule.has_aggregate()) {
+            let sql = sqlite::derivation_sql(rule, tuple, |_, atom| {
+                if self.context.idbs.contains_key(&atom.predicate) {
+                    format!("(SELECT * FROM temp.trace_{} WHERE step < {})", atom.predicate, step)
+                } else {
+                    atom.predicate.clone()
                 }
-                sql.push_str(";");
-                if self.verbose {
-                    println!("{}: {}", "EXECUTE".green(), sql);
+            })?;
+            trace!("EXECUTE: {}", sql);
+            let atoms = rule.body.iter()
+                .filter_map(|clause| match clause {
+                    Clause::Atom(atom) if !atom.negation => Some(&atom.predicate),
+                    _ => None,
+                })
+                .collect::<Vec<&String>>();
+            let type_info = atoms.iter()
+                .flat_map(|predicate| self.analyzer.data_types[*predicate].iter().cloned())
+                .collect::<Vec<DataType>>();
+            let values = self.database.query_row(&sql, [], |row| read_row(row, &type_info)).optional()?;
+            let Some(mut values) = values else {
+                continue;
+            };
+            derivation.rule = Some(index);
+            for predicate in atoms {
+                let rest = values.split_off(self.analyzer.data_types[predicate].len());
+                let premise = self.trace(predicate, &values)?
+                    .expect("Premise of a derivation should hold");
+                derivation.premises.push(premise);
+                values = rest;
+            }
+            return Ok(Some(derivation));
+        }
+        derivation.rule = rules.iter().position(Rule::has_aggregate);
+        Ok(derivation.rule.map(|_| derivation))
+    }
+
+    /// the tuples of a materialized predicate matching an atom after [`Runtime::materialize`],
+    /// e.g. `reachable(a, Y)` gives every tuple of reachable whose first value is `a`.
+    /// `_` matches any value, and unlike a repeated variable, two of them need not be equal,
+    /// e.g. `edge(a, _)` gives every edge from `a`
+    pub fn ask(&self, ask: &str) -> Result<Vec<Tuple>, RuntimeError> {
+        self.query(&self.parse_ask(ask)?)
+    }
+
+    /// parse the atom to ask as a query without a body,
+    /// its predicate is an edb or an idb evaluated by [`Runtime::materialize`]
+    fn parse_ask(&self, ask: &str) -> Result<Rule, RuntimeError> {
+        let invalid = |reason: String| RuntimeError::InvalidAsk { ask: ask.to_string(), reason };
+        let head = parse_atom(ask)
+            .map_err(|error| invalid(format!("expected an atom at column {}", error.column)))?;
+        if head.negation {
+            return Err(invalid("a negated atom cannot be asked".to_string()));
+        }
+        if !self.context.edbs.contains_key(&head.predicate) && !self.context.idbs.contains_key(&head.predicate) {
+            return Err(invalid(format!("{} is not an EDB or an evaluated IDB of the program", head.predicate)));
+        }
+        let arity = self.analyzer.data_types[&head.predicate].len();
+        if head.terms.len() != arity {
+            return Err(invalid(format!("{} has {} columns, but {} terms are given",
+                head.predicate,
+                arity,
+                head.terms.len()
+            )));
+        }
+        Ok(Rule { io: IO::Write(None), head, body: Vec::new(), columns: Vec::new(), disjuncts: Vec::new() })
+    }
+
+    /// parse the traced tuple, written as `predicate,value,...`, by the column types of the predicate
+    fn parse_trace(&self, trace: &str) -> Result<(String, Tuple), RuntimeError> {
+        let mut fields = trace.split(',').map(str::trim);
+        let predicate = fields.next().unwrap_or_default().to_string();
+        let invalid = |reason: String| RuntimeError::InvalidTrace { trace: trace.to_string(), reason };
+        if !self.context.edbs.contains_key(&predicate) && !self.context.idbs.contains_key(&predicate) {
+            return Err(invalid(format!("{} is not an EDB or IDB of the program", predicate)));
+        }
+        let type_info = &self.analyzer.data_types[&predicate];
+        let fields = fields.collect::<Vec<&str>>();
+        if fields.len() != type_info.len() {
+            return Err(invalid(format!("{} has {} columns, but {} values are given",
+                predicate,
+                type_info.len(),
+                fields.len()
+            )));
+        }
+        let tuple = fields.iter()
+            .zip(type_info.iter())
+            .map(|(field, data_type)| {
+                data_type.parse_value(field)
+                    .and_then(|value| value_to_constant(value, data_type).ok())
+                    .ok_or_else(|| invalid(format!("`{}` is not a valid {:?}", field, data_type)))
+            })
+            .collect::<Result<Tuple, RuntimeError>>()?;
+        Ok((predicate, tuple))
+    }
+
+    /// print the queries annotated with @output to stdout,
+    /// and write those annotated with @output(file) to file.csv.
+    pub fn write_queries(&self) -> Result<(), RuntimeError> {
+        if let Format::Json = self.options.format {
+            return self.write_json_queries();
+        }
+        // queries are written in the order of their predicates, as are the keys of the json output
+        let mut queries = self.context.queries.iter().collect::<Vec<(&String, &Vec<Rule>)>>();
+        queries.sort_by_key(|(name, _)| *name);
+        for (_, rules) in queries {
+            for rule in rules {
+                let entities = self.query(rule)?
+                    .iter()
+                    .map(|entity| entity.iter().map(format_value).collect::<Vec<String>>())
+                    .collect::<Vec<Vec<String>>>();
+                if let Some(file) = rule.io.output_file() {
+                    let path = self.source_dir.join(format!("{}.csv", file));
+                    self.write_csv(&path, &self.output_columns(rule), &entities)?;
+                    println!("{}: {} -> {}", "QUERY".green(), rule.head, path.display());
+                    println!("{}: {}", "COUNT".green(), entities.len());
+                    continue;
                 }
-                let mut stmt = self.database.prepare(sql.as_str()).unwrap();
-                let rows = stmt.query_map([], |row| {
-                    let mut values = Vec::new();
-                    for i in 0..rule.head.terms.len() {
-                        let value = row.get::<_, String>(i).unwrap();
-                        values.push(value);
-                    }
-                    Ok(values)
-                }).unwrap();
-                let entities = rows.collect::<Result<Vec<Vec<String>>, _>>().unwrap();
-                // if length of entities is less than 20, print all
-                // else print the first 10 and last 10
+                // if length of entities is at most twice the preview, print all
+                // else print the first and last preview entities, a preview of 0 only prints the count
                 println!("{}: {}", "QUERY".green(), rule.head);
-                if entities.len() <= 20 {
+                let preview = self.options.preview;
+                if entities.len() <= 2 * preview {
                     entities.iter().for_each(|entity| {
                         println!("{}", entity.join(", "));
                     });
-                } else {
-                    entities.iter().take(10).for_each(|entity| {
+                } else if preview > 0 {
+                    entities.iter().take(preview).for_each(|entity| {
                         println!("{}", entity.join(", "));
                     });
                     println!("...");
-                    entities.iter().rev().take(10).for_each(|entity| {
+                    entities.iter().skip(entities.len() - preview).for_each(|entity| {
                         println!("{}", entity.join(", "));
                     });
                 }
                 println!("{}: {}", "COUNT".green(), entities.len());
             }
-        });
+        }
         Ok(())
     }
 
-    fn apply_rules(&self, rules: &Vec<Rule>, previous: &Vec<String>) {
-        let base_cases = rules.iter()
-            .filter(|rule| rule.is_base_case(previous))
-            .collect::<Vec<&Rule>>();
-        base_cases.iter().for_each(|&rule| {
-            // create database tables for head if not present
-            let head_table = &rule.head.predicate;
-            let arity = rule.head.terms.len();
-            let mut sql = format!("CREATE TABLE IF NOT EXISTS {} (", head_table);
-            let type_info = self.analyzer.data_types.get(head_table)
-                .expect("Head table should be present in type info");
-            for i in 0..arity {
-                // get type from analyzer
-                let data_type = type_info.get(i)
-                    .expect("Type info should be present");
-                let type_ = match data_type {
-                    DataType::Integer => "INTEGER",
-                    DataType::Symbol => "TEXT",
-                    DataType::Float => "REAL",
-                };
-                sql.push_str(format!("column_{} {}", i, type_).as_str());
-                if i < arity - 1 {
-                    sql.push_str(", ");
+    /// print the queries annotated with @output to stdout as a single json object keyed by predicate,
+    /// and write those annotated with @output(file) to file.json.
+    fn write_json_queries(&self) -> Result<(), RuntimeError> {
+        let mut results = serde_json::Map::new();
+        for (query, rules) in self.context.queries.iter() {
+            for rule in rules {
+                let entities = relation_to_json(&self.output_columns(rule), &self.query(rule)?);
+                if let Some(file) = rule.io.output_file() {
+                    let path = self.source_dir.join(format!("{}.json", file));
+                    let json = serde_json::to_string_pretty(&entities)
+                        .expect("Json value should be serializable");
+                    std::fs::write(path, json)?;
+                    continue;
                 }
-            }
-            // unique constraint on all columns
-            sql.push_str(", UNIQUE(");
-            for i in 0..arity {
-                sql.push_str(format!("column_{}", i).as_str());
-                if i < arity - 1 {
-                    sql.push_str(", ");
+                if let serde_json::Value::Array(entities) = entities {
+                    let relation = results.entry(query.clone())
+                        .or_insert_with(|| serde_json::Value::Array(Vec::new()));
+                    if let serde_json::Value::Array(relation) = relation {
+                        relation.extend(entities);
+                    }
                 }
             }
-            sql.push_str("));");
-            if self.verbose {
-                println!("{}: {}", "EXECUTE".green(), sql);
+        }
+        let json = serde_json::to_string_pretty(&serde_json::Value::Object(results))
+            .expect("Json value should be serializable");
+        println!("{}", json);
+        Ok(())
+    }
+
+    /// write every entity to a csv file, with the names of the columns as header
+    fn write_csv(&self, path: &Path, columns: &[String], entities: &[Vec<String>]) -> Result<(), RuntimeError> {
+        let file = path.display().to_string();
+        let csv_error = |error| RuntimeError::Csv { file: file.clone(), error };
+        let mut writer = csv::Writer::from_path(path).map_err(csv_error)?;
+        writer.write_record(columns).map_err(csv_error)?;
+        for entity in entities {
+            writer.write_record(entity).map_err(csv_error)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    fn query(&self, rule: &Rule) -> Result<Vec<Tuple>, RuntimeError> {
+        if rule.is_bag() {
+            return self.query_bag(rule);
+        }
+        if !rule.body.is_empty() {
+            return self.query_view(rule);
+        }
+        let query = &rule.head.predicate;
+        if self.is_underived(query)? {
+            return Ok(Vec::new());
+        }
+        let mut sql = format!("SELECT * FROM {}", query);
+        let where_sql = query_filters(rule);
+        if !where_sql.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(where_sql.join(" AND ").as_str());
+        }
+        let type_info = self.analyzer.data_types.get(query)
+            .expect("Query table should be present in type info");
+        self.select(rule, sql, type_info)
+    }
+
+    /// read the tuples of a query selected by `sql`, typed by `type_info`.
+    /// they are ordered by their columns from first to last under the sorted option,
+    /// and always for a query written to a file, so that the output is the same across runs.
+    /// a column is ordered by its type, numerically for numbers and by text for symbols.
+    /// under the limit option, only the first tuples of the order are read
+    fn select(&self, rule: &Rule, mut sql: String, type_info: &[DataType]) -> Result<Vec<Tuple>, RuntimeError> {
+        if self.options.sorted || rule.io.output_file().is_some() {
+            let columns = (0..rule.head.terms.len())
+                .map(|i| format!("column_{}", i))
+                .collect::<Vec<String>>();
+            sql.push_str(&format!(" ORDER BY {}", columns.join(", ")));
+        }
+        if let Some(limit) = self.options.limit {
+            sql.push_str(&format!(" LIMIT {}", limit));
+        }
+        sql.push(';');
+        trace!("EXECUTE: {}", sql);
+        let mut stmt = self.database.prepare(&sql)?;
+        let rows = stmt.query_map([], |row| read_row(row, type_info))?;
+        let entities = rows.collect::<Result<Vec<Tuple>, rusqlite::Error>>()?;
+        Ok(entities)
+    }
+
+    /// the tuples of an @output_bag query, each followed by its number of derivations
+    fn query_bag(&self, rule: &Rule) -> Result<Vec<Tuple>, RuntimeError> {
+        if rule.body.is_empty() && self.is_underived(&rule.head.predicate)? {
+            return Ok(Vec::new());
+        }
+        let mut type_info = self.head_types(rule).to_vec();
+        type_info.push(DataType::Integer);
+        self.select(rule, self.bag_query_sql(rule)?, &type_info)
+    }
+
+    /// the statement selecting the tuples of an @output_bag query with their multiplicity.
+    /// a view counts the matches of its body, an idb the matches of the bodies of its rules
+    /// over the derived relations, and a tuple of an edb is given once
+    fn bag_query_sql(&self, rule: &Rule) -> Result<String, RuntimeError> {
+        let query = &rule.head.predicate;
+        let sql = match self.context.idbs.get(query) {
+            _ if !rule.body.is_empty() => return sqlite::bag_sql(rule),
+            Some(rules) => {
+                let bags = rules.iter().map(sqlite::bag_sql).collect::<Result<Vec<String>, RuntimeError>>()?;
+                sqlite::sum_bags_sql(&bags, rule.head.terms.len())
             }
-            self.database.execute(&sql, params![]).unwrap();
+            None => format!("SELECT *, 1 AS multiplicity FROM {}", query),
+        };
+        let where_sql = query_filters(rule);
+        if where_sql.is_empty() {
+            return Ok(sql);
+        }
+        Ok(format!("SELECT * FROM ({}) WHERE {}", sql, where_sql.join(" AND ")))
+    }
+
+    /// whether a query without a body is on a predicate neither read nor derived by any rule,
+    /// it has no table and no tuples, which is warned about rather than raised
+    fn is_underived(&self, query: &str) -> Result<bool, RuntimeError> {
+        if self.has_table(query)? {
+            return Ok(false);
+        }
+        eprintln!("{}: {} is neither an EDB nor derived by any rule, its query has no result", "WARNING".yellow(), query);
+        Ok(true)
+    }
+
+    /// whether a table is present, in memory or in the attached on-disk database
+    fn has_table(&self, name: &str) -> Result<bool, RuntimeError> {
+        let sql = "SELECT COUNT(*) FROM pragma_table_info(?1)";
+        let columns: i64 = self.database.query_row(sql, params![name], |row| row.get(0))?;
+        Ok(columns > 0)
+    }
+
+    /// the names of the columns written for a query, with the multiplicity last under bag semantics
+    fn output_columns(&self, rule: &Rule) -> Vec<String> {
+        let mut columns = self.analyzer.columns(&rule.head.predicate);
+        if rule.is_bag() {
+            columns.push("multiplicity".to_string());
+        }
+        columns
+    }
+
+    /// derive a view, i.e. an @output rule with a body, into a temporary table,
+    /// and read it before dropping the table
+    fn query_view(&self, rule: &Rule) -> Result<Vec<Tuple>, RuntimeError> {
+        let table = format!("temp.{}", rule.head.predicate);
+        self.execute(&sqlite::create_table_sql(&table, self.head_types(rule)))?;
+        self.execute(&sqlite::build_rule_sql(rule, &VarDict::new(rule), &table, Mode::Base)?)?;
+        let entities = self.select(rule, format!("SELECT * FROM {}", table), self.head_types(rule))?;
+        self.execute(&sqlite::drop_sql(&table))?;
+        Ok(entities)
+    }
+
+    /// every tuple of a table, typed by the inferred column types
+    fn read_relation(&self, table: &str) -> Result<Vec<Tuple>, RuntimeError> {
+        let type_info = self.analyzer.data_types.get(table)
+            .expect("Table should be present in type info");
+        let mut stmt = self.database.prepare(&format!("SELECT * FROM {}", table))?;
+        let rows = stmt.query_map([], |row| read_row(row, type_info))?;
+        let tuples = rows.collect::<Result<Vec<Tuple>, rusqlite::Error>>()?;
+        Ok(tuples)
+    }
+
+    /// replace the table of an idb by the tuples derived by another evaluator
+    fn store_relation(&self, table: &str, tuples: Vec<Tuple>) -> Result<(), RuntimeError> {
+        let type_info = self.analyzer.data_types.get(table)
+            .expect("IDB should be present in type info");
+        self.execute(&format!("DROP TABLE IF EXISTS {}", table))?;
+        self.sqlite().create_relation(table, type_info)?;
+        self.bulk_insert(table, &tuples)
+    }
+
+    fn apply_rules(
+        &self,
+        evaluator: &mut dyn Evaluator,
+        rules: &[Rule],
+        previous: &[String],
+        stats: &mut Stats
+    ) -> Result<(), RuntimeError> {
+        // create relation for head if not present, even if no base case derives a tuple,
+        // so that an idb deriving nothing is an empty table rather than a missing one
+        for rule in rules {
+            evaluator.create_relation(&rule.head.predicate, self.head_types(rule))?;
+        }
+        let base_cases = rules.iter()
+            .filter(|rule| rule.is_base_case(previous))
+            .collect::<Vec<&Rule>>();
+        for rule in base_cases {
             // retrieve tuples from edb according to rule
-            self.init_base(rule);
-        });
+            evaluator.select_into(rule, self.var_dict(rule), &rule.head.predicate, Mode::Base)?;
+            self.record_derived(&rule.head.predicate, &rule.head.predicate)?;
+        }
 
+        // the recursive rules of a predicate reach a fixpoint together,
+        // so that the tuples derived by one rule are read by the others
         let recursive_cases = rules.iter()
             .filter(|rule| !rule.is_base_case(previous))
             .collect::<Vec<&Rule>>();
-        recursive_cases.iter().for_each(|&rule| {
-            self.semi_naive_evaluate(rule);
-        });
+        if !recursive_cases.is_empty() {
+            self.semi_naive_evaluate(evaluator, &recursive_cases, stats)?;
+        }
+        Ok(())
     }
 
-    fn init_base(&self, rule: &Rule) {
-        let indent = " ".repeat(9);
-        let mut sql = format!("INSERT OR IGNORE INTO {}\n", rule.head.to_string());
-        let mut select_sql = Vec::new();
-        let mut join_sql = HashMap::new();
-        let mut where_sql = Vec::new();
-        let mut first_predicate = String::new();
-        let var_dict = VarDict::new(&rule);
-        let mut distinguished_variables: Vec<HashSet::<(usize, usize)>> = Vec::new();
-        distinguished_variables.resize(rule.head.terms.len(), HashSet::new());
-        rule.head.terms.iter().enumerate().for_each(|(i, term)| {
-            if let Some(var) = term.is_nontrivial_variable() {
-                distinguished_variables[i] = var_dict.alloc(&var);
-            }
-        });
-        // push select_sql stmts
-        distinguished_variables.iter().enumerate().for_each(|(index, set)| {
-            if set.is_empty() {
-                panic!("Variable {} is not assigned", rule.head.terms[index]);
-            }
-            let (atom_index, term_index) = set.iter()
-                .min_by_key(|(_, term_index)| term_index).unwrap();
-            let atom_name = &rule.body[*atom_index].to_string();
-            first_predicate = atom_name.clone();
-            let stmt = String::from(format!("{}.column_{} AS column_{}", atom_name, term_index, index));
-            select_sql.push(stmt);
-        });
-        // push inner where_sql stmts
-        var_dict.clause_dict.iter().for_each(|(_, var_groups)| {
-            var_groups.iter().for_each(|group| {
-                if group.contain_duplicate() {
-                    let atom_predicate = rule.body[group.clause_index].to_string();
-                    let positions = &group.term_indexes;
-                    positions.iter().skip(1).for_each(|position| {
-                        let stmt = format!("{}.column_{} = {}.column_{}",
-                            atom_predicate,
-                            positions[0],
-                            atom_predicate,
-                            position);
-                        where_sql.push(stmt);
-                    });
-                }
-            });
-        });
-        // push constant where_sql stmts
-        rule.body.iter().for_each(|clause| {
-            if let Clause::Atom(atom) = clause {
-                atom.terms.iter().enumerate().for_each(|(term_index, term)| {
-                    if let Term::Constant(constant) = term {
-                        let stmt = format!("{}.column_{} = {}",
-                            atom.predicate,
-                            term_index,
-                            constant);
-                        where_sql.push(stmt);
-                    }
-                });
-            }
-        });
-        // push join_sql stmts
-        var_dict.clause_dict.iter().for_each(|(_, var_groups)| {
-            if var_groups.len() > 1 {
-                let anchor = var_groups[0].clause_index;
-                let anchor = &rule.body[anchor].to_string();
-                let anchor_term_index = var_groups[0].term_indexes[0];
-                var_groups.iter().skip(1).for_each(|group| {
-                    let atom_predicate = rule.body[group.clause_index].to_string();
-                    let positions = &group.term_indexes;
-                    let stmt = format!("{}.column_{} = {}.column_{}",
-                        anchor,
-                        anchor_term_index,
-                        atom_predicate,
-                        positions[0]);
-                    if *anchor != first_predicate {
-                        join_sql.entry(anchor.clone()).or_insert(Vec::new()).push(stmt.clone());
-                    }
-                    if atom_predicate != first_predicate {
-                        join_sql.entry(atom_predicate).or_insert(Vec::new()).push(stmt.clone());
-                    }
-                });
+    /// under the trace option, record the tuples of `source` first derived into `predicate`
+    /// with the current step in `temp.trace_predicate`,
+    /// so that a tuple is only explained by tuples recorded at an earlier step
+    fn record_derived(&self, predicate: &str, source: &str) -> Result<(), RuntimeError> {
+        if self.options.trace.is_none() {
+            return Ok(());
+        }
+        let type_info = self.analyzer.data_types.get(predicate)
+            .expect("IDB should be present in type info");
+        let columns = type_info.iter()
+            .enumerate()
+            .map(|(i, data_type)| format!("column_{} {}", i, data_type.sql_type()))
+            .collect::<Vec<String>>();
+        let unique = (0..type_info.len())
+            .map(|i| format!("column_{}", i))
+            .collect::<Vec<String>>();
+        self.execute(&format!("CREATE TABLE IF NOT EXISTS temp.trace_{} ({}, step INTEGER, UNIQUE({}))",
+            predicate,
+            columns.join(", "),
+            unique.join(", ")
+        ))?;
+        self.execute(&format!("INSERT OR IGNORE INTO temp.trace_{} SELECT *, {} FROM {}",
+            predicate,
+            self.step.get(),
+            source
+        ))?;
+        self.step.set(self.step.get() + 1);
+        Ok(())
+    }
+
+    /// the sqlite evaluator on the database of the runtime
+    fn sqlite(&self) -> SqliteEvaluator<'_> {
+        SqliteEvaluator {
+            database: &self.database,
+            disk: &self.options.disk,
+            diff: self.options.diff,
+        }
+    }
+
+    /// log the statement at the trace level, then execute it
+    fn execute(&self, sql: &str) -> Result<(), RuntimeError> {
+        self.sqlite().execute(sql)
+    }
+
+    /// the [`VarDict`] of a rule of an idb, built by the analyzer
+    fn var_dict(&self, rule: &Rule) -> &VarDict {
+        let index = self.context.idbs[&rule.head.predicate].iter()
+            .position(|idb| std::ptr::eq(idb, rule))
+            .expect("Rule should be a rule of its idb");
+        &self.analyzer.var_dicts[&rule.head.predicate][index]
+    }
+
+    /// the inferred column types of the head of a rule
+    fn head_types(&self, rule: &Rule) -> &[DataType] {
+        self.analyzer.data_types.get(&rule.head.predicate)
+            .expect("Head table should be present in type info")
+    }
+
+    /// evaluate the recursive rules of a predicate until none of them derives a new tuple
+    fn semi_naive_evaluate(&self, evaluator: &mut dyn Evaluator, rules: &[&Rule], stats: &mut Stats) -> Result<(), RuntimeError> {
+        let head = &rules[0].head.predicate;
+        let (delta, temp) = (delta_relation(head), temp_relation(head));
+        // copy the relation into the delta, and create an empty temp relation,
+        // both are dropped by the guard even if the evaluation is interrupted by an error
+        let mut scratch = ScratchRelations::new(evaluator);
+        scratch.copy_relation(head, &delta)?;
+        scratch.create_relation(&temp, self.head_types(rules[0]))?;
+        let evaluator = &mut *scratch.evaluator;
+        let mut statements = Vec::new();
+        for rule in rules {
+            let var_dict = self.var_dict(rule);
+            evaluator.prepare(rule, var_dict)?;
+            for mode in self.iteration_modes(rule) {
+                statements.push((*rule, var_dict, mode));
             }
-        });
-        let mut select_sql = select_sql.join(", ");
-        select_sql = format!("{}SELECT {}\n{}FROM {}\n", indent, select_sql, indent, first_predicate);
-        sql.push_str(&select_sql);
-        if !join_sql.is_empty() {
-            join_sql.iter().for_each(|(predicate, stmts)| {
-                let mut stmts = stmts.join(" AND ");
-                stmts = format!("{}JOIN {} ON {}\n", indent, predicate, stmts);
-                sql.push_str(&stmts);
-            });
         }
-        if !where_sql.is_empty() {
-            let mut where_sql = where_sql.join(" AND ");
-            where_sql = format!("{}WHERE {}\n", indent, where_sql);
-            sql.push_str(&where_sql);
-        }
-        if self.verbose {
-            println!("{}: {}", "EXECUTE".green(), sql);
-        }
-        self.database.execute(&sql, params![]).unwrap();
-    }
-
-    fn semi_naive_evaluate(&self, rule: &Rule) {
-        // copy rule to delta table
-        let delta_table = format!("delta_{}", rule.head.predicate);
-        let init_delta = format!("CREATE TABLE {} AS SELECT * FROM {}",
-            delta_table,
-            rule.head.predicate
-        );
-        if self.verbose {
-            println!("{}: {}", "EXECUTE".green(), init_delta);
-        }
-        self.database.execute(&init_delta, params![]).unwrap();
-        let temp_table = format!("temp_{}", rule.head.predicate);
-        // create empty temp table
-        let create_sql = format!("CREATE TABLE {} AS SELECT * FROM {} WHERE 1 = 0",
-            temp_table,
-            rule.head.predicate
-        );
-        if self.verbose {
-            println!("{}: {}", "EXECUTE".green(), create_sql);
-        }
-        self.database.execute(&create_sql, params![]).unwrap();
-        // evaluate rule util reaching fixpoint
+        // evaluate the rules until reaching fixpoint
         let mut fixpoint = false;
         let mut iterate_counter = 0;
         while !fixpoint {
-            if self.verbose {
-                println!("{}: {}({})", "ITERATE".yellow(), rule.head.predicate, iterate_counter.to_string().yellow());
-            }
-            self.iteration(rule);
-            let count_sql = format!("SELECT COUNT(*) FROM {}", delta_table);
-            let count: i64 = self.database.query_row(
-                &count_sql,
-                params![],
-                |row| row.get(0)
-            ).unwrap();
+            debug!("ITERATE: {}({})", head, iterate_counter);
+            // operations of an iteration are committed at once rather than one by one
+            // temp := rule(delta) for each rule and each recursive atom of it,
+            // delta := temp - original, original := original + delta
+            evaluator.begin()?;
+            for (rule, var_dict, mode) in statements.iter() {
+                evaluator.select_into(rule, var_dict, &temp, *mode)?;
+            }
+            evaluator.diff(&delta, &temp, head, rules[0].head.terms.len())?;
+            evaluator.insert_distinct(head, &delta)?;
+            self.record_derived(head, &delta)?;
+            let count = evaluator.count(&delta)?;
+            evaluator.commit()?;
+            stats.record(head, count);
+            trace!("COMMIT: {}({})", head, iterate_counter);
             fixpoint = count == 0;
             if !fixpoint {
                 iterate_counter += 1;
-            } else {
-                if self.verbose {
-                    println!("{}: {}({})", "FIXPOINT".yellow(), rule.head.predicate, iterate_counter.to_string().green());
+                if let Some(limit) = self.options.max_iterations {
+                    if iterate_counter >= limit {
+                        return Err(RuntimeError::IterationLimitExceeded {
+                            predicate: head.clone(),
+                            limit,
+                        });
+                    }
                 }
+            } else {
+                debug!("FIXPOINT: {}({})", head, iterate_counter);
             }
         }
-        // drop delta table and temp table
-        let drop_delta = format!("DROP TABLE {};", delta_table);
-        if self.verbose {
-            println!("{}: {}", "EXECUTE".green(), drop_delta);
+        // drop delta and temp relation
+        scratch.drop_all()
+    }
+
+    /// the modes of the statements of an iteration of a recursive rule, as decided by the strategy
+    fn iteration_modes(&self, rule: &Rule) -> Vec<Mode> {
+        match self.options.strategy {
+            Strategy::SemiNaive => delta_modes(rule),
+            Strategy::Naive => vec![Mode::Base],
+        }
+    }
+
+    /// the plans of the statements of an iteration of every recursive rule after [`Runtime::materialize`],
+    /// e.g. to check that the indexes on the columns joined by a rule are used.
+    /// the delta and temporary tables of each rule, and their indexes, are created for its plans and dropped afterwards,
+    /// even if a plan cannot be explained. the indexes on the other tables of a rule persist,
+    /// as they do after [`Runtime::materialize`] has created them
+    ///
+    /// ```
+    /// use amoeba::engine::{Runtime, Options};
+    /// use amoeba::syntax::parse;
+    /// use std::collections::HashMap;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let context = parse("
+    ///     edge(a, b). edge(b, c).
+    ///     reachable(X, Y) :- edge(X, Y)
+    ///     reachable(X, Z) :- edge(X, Y), reachable(Y, Z)
+    /// ")?;
+    /// let runtime = Runtime::from_facts(context, HashMap::new(), Options::default())?;
+    /// runtime.materialize()?;
+    /// let plans = runtime.explain_plan()?;
+    /// assert_eq!(plans.len(), 1);
+    /// assert_eq!(plans[0].rule, 1);
+    /// assert!(plans[0].plan.iter().any(|row| row.contains("USING INDEX")));
+    /// // the delta and temporary tables are dropped, so that the plans may be explained again
+    /// assert_eq!(runtime.explain_plan()?.len(), 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn explain_plan(&self) -> Result<Vec<QueryPlan>, RuntimeError> {
+        let mut plans = Vec::new();
+        let mut previous = self.context.edbs
+            .keys()
+            .cloned()
+            .collect::<Vec<String>>();
+        for name in self.context.ordered_idbs() {
+            let rules = self.context.idbs.get(&name)
+                .expect("IDB should be present in context");
+            for (index, rule) in rules.iter().enumerate().filter(|(_, rule)| !rule.is_base_case(&previous)) {
+                let head = &rule.head.predicate;
+                let (delta, temp) = (delta_relation(head), temp_relation(head));
+                let mut sqlite = self.sqlite();
+                let mut scratch = ScratchRelations::new(&mut sqlite);
+                scratch.copy_relation(head, &delta)?;
+                scratch.create_relation(&temp, self.head_types(rule))?;
+                scratch.evaluator.prepare(rule, self.var_dict(rule))?;
+                for mode in self.iteration_modes(rule) {
+                    let sql = sqlite::build_rule_sql(rule, self.var_dict(rule), &temp, mode)?;
+                    let plan = self.query_plan(&sql)?;
+                    plans.push(QueryPlan { predicate: head.clone(), rule: index, sql, plan });
+                }
+                scratch.drop_all()?;
+            }
+            previous.push(name);
         }
-        self.database.execute(&drop_delta, params![]).unwrap();
-        let drop_temp = format!("DROP TABLE {};", temp_table);
-        if self.verbose {
-            println!("{}: {}", "EXECUTE".green(), drop_temp);
+        Ok(plans)
+    }
+
+    /// the rows of `EXPLAIN QUERY PLAN` of a statement, the detail of a row is indented under its parent
+    fn query_plan(&self, sql: &str) -> Result<Vec<String>, RuntimeError> {
+        let mut stmt = self.database.prepare(&format!("EXPLAIN QUERY PLAN {}", sql))?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?, row.get::<_, String>(3)?)))?;
+        let mut depths = HashMap::new();
+        let mut plan = Vec::new();
+        for row in rows {
+            let (id, parent, detail) = row?;
+            let depth = depths.get(&parent).map_or(0, |depth| depth + 1);
+            depths.insert(id, depth);
+            plan.push(format!("{}{}", "  ".repeat(depth), detail));
         }
-        self.database.execute(&drop_temp, params![]).unwrap();
+        Ok(plan)
     }
 
-    fn iteration(&self, rule: &Rule) {
-        let indent = " ".repeat(9);
-        let mut sql = format!("INSERT OR IGNORE INTO temp_{}\n", rule.head.to_string());
-        let mut select_sql = Vec::new();
-        let mut join_sql: HashMap<String, Vec<String>> = HashMap::new();
-        let mut where_sql: Vec<String> = Vec::new();
-        let mut first_predicate = String::new();
-        let var_dict = VarDict::new(rule);
-        let mut distinguished_variables: Vec<HashSet::<(usize, usize)>> = Vec::new();
-        distinguished_variables.resize(rule.head.terms.len(), HashSet::new());
-        rule.head.terms.iter().enumerate().for_each(|(i, term)| {
-            if let Some(var) = term.is_nontrivial_variable() {
-                distinguished_variables[i] = var_dict.alloc(&var);
+    /// the statements evaluating every idb stratum by stratum, without executing any of them.
+    /// the iteration of a recursive rule is repeated until its delta table is empty.
+    pub fn explain(&self) -> Result<String, RuntimeError> {
+        let mut explain = Vec::new();
+        let mut previous = self.context.edbs
+            .keys()
+            .cloned()
+            .collect::<Vec<String>>();
+        for name in self.context.ordered_idbs() {
+            let rules = self.context.idbs.get(&name)
+                .expect("IDB should be present in context");
+            let (base_cases, recursive_cases): (Vec<&Rule>, Vec<&Rule>) = rules.iter()
+                .partition(|rule| rule.is_base_case(&previous));
+            for rule in base_cases {
+                explain.push(format!("-- {}", rule.head));
+                explain.push(sqlite::create_table_sql(&self.sqlite().table(&rule.head.predicate), self.head_types(rule)));
+                explain.push(format!("{};", sqlite::build_rule_sql(rule, self.var_dict(rule), &rule.head.predicate, Mode::Base)?));
             }
-        });
-        // push select_sql stmts
-        distinguished_variables.iter().enumerate().for_each(|(index, set)| {
-            if set.is_empty() {
-                panic!("Variable {} is not assigned", rule.head.terms[index]);
-            }
-            let (atom_index, term_index) = set.iter()
-                .min_by_key(|(_, term_index)| term_index).unwrap();
-            let mut atom_name = rule.body[*atom_index].to_string();
-            if atom_name == rule.head.predicate {
-                atom_name = format!("delta_{}", atom_name);
-            }
-            first_predicate = atom_name.clone();
-            let stmt = String::from(format!("{}.column_{} AS column_{}", atom_name, term_index, index));
-            select_sql.push(stmt);
-        });
-        // push inner where_sql stmts
-        var_dict.clause_dict.iter().for_each(|(_, var_groups)| {
-            var_groups.iter().for_each(|group| {
-                if group.contain_duplicate() {
-                    let mut atom_predicate = rule.body[group.clause_index].to_string();
-                    if atom_predicate == rule.head.predicate {
-                        atom_predicate = format!("delta_{}", atom_predicate);
-                    }
-                    let positions = &group.term_indexes;
-                    positions.iter().skip(1).for_each(|position| {
-                        let stmt = format!("{}.column_{} = {}.column_{}",
-                            atom_predicate,
-                            positions[0],
-                            atom_predicate,
-                            position);
-                        where_sql.push(stmt);
-                    });
+            for rule in recursive_cases {
+                let head = &rule.head.predicate;
+                let (delta, temp) = (delta_relation(head), temp_relation(head));
+                explain.push(format!("-- {}", rule.head));
+                explain.push(format!("{};", sqlite::copy_sql(head, &delta)));
+                explain.push(sqlite::create_table_sql(&temp, self.head_types(rule)));
+                for sql in sqlite::index_sql(rule, self.var_dict(rule), &self.options.disk) {
+                    explain.push(format!("{};", sql));
                 }
-            });
-        });
-        // push constant where_sql stmts
-        rule.body.iter().for_each(|clause| {
-            if let Clause::Atom(atom) = clause {
-                atom.terms.iter().enumerate().for_each(|(term_index, term)| {
-                    if let Term::Constant(constant) = term {
-                        let mut atom_predicate = atom.predicate.clone();
-                        if atom_predicate == rule.head.predicate {
-                            atom_predicate = format!("delta_{}", atom_predicate);
-                        }
-                        let stmt = format!("{}.column_{} = {}",
-                            atom_predicate,
-                            term_index,
-                            constant);
-                        where_sql.push(stmt);
-                    }
-                });
-            }
-        });
-        // push join_sql stmts
-        var_dict.clause_dict.iter().for_each(|(_, var_groups)| {
-            if var_groups.len() > 1 {
-                let anchor = var_groups[0].clause_index;
-                let mut anchor = rule.body[anchor].to_string();
-                if anchor == rule.head.predicate {
-                    anchor = format!("delta_{}", anchor);
+                explain.push(format!("-- repeat until {} is empty", delta));
+                for mode in self.iteration_modes(rule) {
+                    explain.push(format!("{};", sqlite::build_rule_sql(rule, self.var_dict(rule), &temp, mode)?));
                 }
-                let anchor_term_index = var_groups[0].term_indexes[0];
-                var_groups.iter().skip(1).for_each(|group| {
-                    let mut atom_predicate = rule.body[group.clause_index].to_string();
-                    if atom_predicate == rule.head.predicate {
-                        atom_predicate = format!("delta_{}", atom_predicate);
-                    }
-                    let positions = &group.term_indexes;
-                    let stmt = format!("{}.column_{} = {}.column_{}",
-                        anchor,
-                        anchor_term_index,
-                        atom_predicate,
-                        positions[0]);
-                    if *anchor != first_predicate {
-                        join_sql.entry(anchor.clone()).or_insert(Vec::new()).push(stmt.clone());
-                    }
-                    if atom_predicate != first_predicate {
-                        join_sql.entry(atom_predicate).or_insert(Vec::new()).push(stmt.clone());
-                    }
-                });
+                for sql in sqlite::diff_sql(&delta, &temp, head, rule.head.terms.len(), self.options.diff) {
+                    explain.push(format!("{};", sql));
+                }
+                explain.push(sqlite::union_sql(head, &delta));
+                explain.push(sqlite::drop_sql(&delta));
+                explain.push(sqlite::drop_sql(&temp));
             }
-        });
-        let mut select_sql = select_sql.join(", ");
-        select_sql = format!("{}SELECT {}\n{}FROM {}", indent, select_sql, indent, first_predicate);
-        sql.push_str(&select_sql);
-        if !join_sql.is_empty() {
-            join_sql.iter().for_each(|(predicate, stmts)| {
-                let mut stmts = stmts.join(" AND ");
-                stmts = format!("\n{}JOIN {} ON {}", indent, predicate, stmts);
-                sql.push_str(&stmts);
+            previous.push(name);
+        }
+        for rules in self.context.queries.values() {
+            for rule in rules.iter().filter(|rule| rule.is_bag()) {
+                explain.push(format!("-- @output_bag {}", rule.head));
+                let sql = self.bag_query_sql(rule)?;
+                explain.push(format!("{};", sql));
+            }
+            for rule in rules.iter().filter(|rule| !rule.body.is_empty() && !rule.is_bag()) {
+                let table = format!("temp.{}", rule.head.predicate);
+                explain.push(format!("-- @output {}", rule.head));
+                explain.push(sqlite::create_table_sql(&table, self.head_types(rule)));
+                explain.push(format!("{};", sqlite::build_rule_sql(rule, &VarDict::new(rule), &table, Mode::Base)?));
+                explain.push(sqlite::drop_sql(&table));
+            }
+        }
+        Ok(explain.join("\n"))
+    }
+}
+
+/// the file of an @input(file) edb, file.csv, or file.json if there is no csv file
+fn input_path(source_dir: &Path, file: &str) -> PathBuf {
+    let csv = source_dir.join(format!("{}.csv", file));
+    let json = source_dir.join(format!("{}.json", file));
+    if !csv.exists() && json.exists() {
+        return json;
+    }
+    csv
+}
+
+/// every edb missing from the database, declared with another arity than its table,
+/// or whose csv or json file is missing, in the order of their names.
+/// an edb with inline facts may have no table, it is created when loading them
+fn check_edbs(context: &Context, database: &Connection, source_dir: &Path) -> Result<Vec<RuntimeError>, RuntimeError> {
+    let mut errors = Vec::new();
+    let mut edbs = context.edbs.iter().collect::<Vec<(&String, &Rule)>>();
+    edbs.sort_by_key(|(table, _)| *table);
+    for (table, rule) in edbs {
+        // edbs annotated with @input(file) are loaded from file.csv or file.json instead
+        if let IO::Read(Some(file)) = &rule.io {
+            let path = input_path(source_dir, file);
+            if let Err(error) = std::fs::File::open(&path) {
+                errors.push(RuntimeError::Csv { file: path.display().to_string(), error: error.into() });
+            }
+            continue;
+        }
+        let sql = format!("SELECT name FROM sqlite_master WHERE type='table' AND name='{}';", table);
+        if !database.prepare(&sql)?.exists(params![])? {
+            if !context.facts.contains_key(table) {
+                errors.push(RuntimeError::MissingEdb(table.clone()));
+            }
+            continue;
+        }
+        // check if edb table has the same arity as in the rule
+        let arity = rule.head.terms.len();
+        let mut stmt = database.prepare(&format!("PRAGMA table_info({})", table))?;
+        let actual_arity = stmt.query_map(params![], |row| row.get::<_, String>(1))?.count();
+        if arity != actual_arity {
+            errors.push(RuntimeError::ArityMismatch {
+                table: table.clone(),
+                expected: arity,
+                actual: actual_arity,
             });
         }
-        if !where_sql.is_empty() {
-            let mut where_sql = where_sql.join(" AND ");
-            where_sql = format!("\n{}WHERE {}", indent, where_sql);
-            sql.push_str(&where_sql);
-        }
-        if self.verbose {
-            println!("{}: {}", "EXECUTE".green(), sql);
-        }
-        self.database.execute(&sql, params![]).unwrap();
-
-        // update delta := temp - original
-        let clear_delta = format!("DELETE FROM delta_{}", rule.head.predicate);
-        if self.verbose {
-            println!("{}: {}", "EXECUTE".green(), clear_delta);
-        }
-        self.database.execute(&clear_delta, params![]).unwrap();
-        // use left join
-        let mut update_sql = format!("INSERT OR IGNORE INTO delta_{}\n{}SELECT temp_{}.* FROM temp_{}\n{}",
-            rule.head.predicate,
-            indent,
-            rule.head.predicate,
-            rule.head.predicate,
-            indent,
-        );
-        let wheres: Vec<String> = (0..distinguished_variables.len()).map(|i| format!("column_{}", i)).collect();
-        // LEFT JOIN original ON temp.column_0 = original.column_0 AND ...
-        // WHERE original.column_0 IS NULL AND ...
-        update_sql.push_str(&format!("LEFT JOIN {} ON {}\n",
-            rule.head.predicate,
-            wheres.iter().enumerate().map(|(_, where_)| {
-                format!("temp_{}.{} = {}.{}", rule.head.predicate, where_, rule.head.predicate, where_)
-            }).collect::<Vec<String>>().join(" AND "),
-        ));
-        update_sql.push_str(&format!("{}WHERE {}",
-            indent,
-            wheres.iter().enumerate().map(|(_, where_)| {
-                format!("{}.{} IS NULL", rule.head.predicate, where_)
-            }).collect::<Vec<String>>().join(" AND "),
-        ));
-        if self.verbose {
-            println!("{}: {}", "EXECUTE".green(), update_sql);
-        }
-        self.database.execute(&update_sql, params![]).unwrap();
-
-        // update original := original + delta
-        let update_sql = format!("INSERT OR IGNORE INTO {}\n{}SELECT * FROM delta_{};",
-            rule.head.predicate,
-            indent,
-            rule.head.predicate,
-        );
-        if self.verbose {
-            println!("{}: {}", "EXECUTE".green(), update_sql);
-        }
-        self.database.execute(&update_sql, params![]).unwrap();
     }
+    Ok(errors)
+}
+
+/// rewrite a program as configured by the options, and infer the column types of its predicates
+fn analyze(mut context: Context, options: &Options) -> Result<(Context, Analyzer), RuntimeError> {
+    // the tables of predicates never collide with scratch relations or idbs written back under a prefix
+    let predicates = context.edbs.keys().chain(context.idbs.keys()).chain(context.queries.keys());
+    for predicate in predicates {
+        if let Some(prefix) = RESERVED_PREFIXES.iter().find(|prefix| predicate.starts_with(*prefix)) {
+            return Err(RuntimeError::ReservedPrefix {
+                predicate: predicate.clone(),
+                prefix: prefix.to_string(),
+            });
+        }
+    }
+    if let Some(name) = options.disk.iter().find(|name| !context.idbs.contains_key(*name)) {
+        return Err(RuntimeError::UnknownIdb(name.clone()));
+    }
+    if options.magic {
+        context = magic::rewrite(&context)?;
+    }
+    if let Some(query) = &options.query {
+        if !context.queries.contains_key(query) {
+            let mut available = context.queries.keys().cloned().collect::<Vec<String>>();
+            available.sort();
+            return Err(RuntimeError::UnknownQuery { query: query.clone(), available });
+        }
+        context.select_query(query);
+    }
+    let mut analyzer = Analyzer::new();
+    analyzer.type_inference(&context)?;
+    if let Some(epsilon) = options.epsilon {
+        analyzer.approximate_float_equality(&mut context, epsilon)?;
+    }
+    analyzer.build_var_dicts(&context);
+    Ok((context, analyzer))
+}
+
+/// an iteration of a recursive rule is the union of one variant per atom of the head predicate,
+/// in which that atom reads the delta and every other atom reads the full relation
+fn delta_modes(rule: &Rule) -> Vec<Mode> {
+    let modes = rule.body.iter()
+        .enumerate()
+        .filter(|(_, clause)| matches!(clause, Clause::Atom(atom) if atom.predicate == rule.head.predicate))
+        .map(|(clause_index, _)| Mode::Delta(clause_index))
+        .collect::<Vec<Mode>>();
+    if modes.is_empty() { vec![Mode::Base] } else { modes }
 }
 
+/// the conditions on the table of a query without a body,
+/// a constant term selects its value and a repeated variable equal columns
+fn query_filters(rule: &Rule) -> Vec<String> {
+    let mut where_sql = Vec::new();
+    let var_dict = VarDict::new(rule);
+    // push constant terms to where clause
+    rule.head.terms.iter().enumerate().for_each(|(term_index, term)| {
+        if let Term::Constant(constant) = term {
+            let column = format!("column_{}", term_index);
+            where_sql.push(format!("{} = {}", column, constant.to_sql()));
+        }
+    });
+    // every occurrence of a variable equals its first occurrence
+    var_dict.head_dict.iter().for_each(|(_, indexes)| {
+        indexes.iter().skip(1).for_each(|index| {
+            where_sql.push(format!("column_{} = column_{}", indexes[0], index));
+        });
+    });
+    where_sql
+}
+
+/// format a value for output, symbols are not quoted
+/// and booleans are written as true or false
+fn format_value(value: &Constant) -> String {
+    match value {
+        Constant::Symbol(symbol) => symbol.clone(),
+        Constant::Boolean(boolean) => boolean.to_string(),
+        value => value.to_string(),
+    }
+}
+
+/// a relation as an array of objects keyed by the names of its columns
+fn relation_to_json(columns: &[String], entities: &[Tuple]) -> serde_json::Value {
+    let entities = entities.iter().map(|entity| {
+        let object = entity.iter()
+            .zip(columns.iter())
+            .map(|(value, column)| (column.clone(), value_to_json(value)))
+            .collect::<serde_json::Map<String, serde_json::Value>>();
+        serde_json::Value::Object(object)
+    }).collect();
+    serde_json::Value::Array(entities)
+}
+
+/// integers and floats are written as numbers, symbols as strings and booleans as booleans
+fn value_to_json(value: &Constant) -> serde_json::Value {
+    match value {
+        Constant::Integer(integer) => serde_json::Value::from(*integer),
+        // an infinite float has no json representation
+        Constant::Float(float) => serde_json::Number::from_f64(float.into_inner())
+            .map_or(serde_json::Value::Null, serde_json::Value::Number),
+        Constant::Symbol(symbol) => serde_json::Value::String(symbol.clone()),
+        Constant::Boolean(boolean) => serde_json::Value::Bool(*boolean),
+    }
+}
+
+/// a value of a json record as a constant of the column type, none if it is not of that type
+fn json_to_constant(value: &serde_json::Value, data_type: &DataType) -> Option<Constant> {
+    match (data_type, value) {
+        (DataType::Integer, value) => value.as_i64().map(Constant::Integer),
+        (DataType::Float, value) => value.as_f64().and_then(|value| NotNan::new(value).ok()).map(Constant::Float),
+        (DataType::Symbol, serde_json::Value::String(value)) => Some(Constant::Symbol(value.clone())),
+        (DataType::Boolean, serde_json::Value::Bool(value)) => Some(Constant::Boolean(*value)),
+        _ => None,
+    }
+}
+
+/// read a row of a table, typed by its column types
+fn read_row(row: &Row, type_info: &[DataType]) -> rusqlite::Result<Tuple> {
+    let mut values = Vec::new();
+    for (i, data_type) in type_info.iter().enumerate() {
+        let value = match (data_type, row.get_ref(i)?) {
+            (DataType::Integer, value) => Constant::Integer(value.as_i64()?),
+            (DataType::Float, ValueRef::Integer(value)) => Constant::Float(NotNan::new(value as f64).unwrap()),
+            (DataType::Float, value) => Constant::Float(NotNan::new(value.as_f64()?).unwrap()),
+            (DataType::Symbol, ValueRef::Integer(value)) => Constant::Symbol(value.to_string()),
+            (DataType::Symbol, ValueRef::Real(value)) => Constant::Symbol(value.to_string()),
+            (DataType::Symbol, value) => Constant::Symbol(value.as_str()?.to_string()),
+            (DataType::Boolean, value) => Constant::Boolean(value.as_i64()? != 0),
+        };
+        values.push(value);
+    }
+    Ok(values)
+}
+
+/// a value of the database as a constant of a column type, a text is parsed as a value of that type.
+/// none for a null, a blob, a real with a fraction in an int column, or a text which does not parse, e.g. `nan` in a float column
+fn coerce_value(value: ValueRef, data_type: &DataType) -> Option<Constant> {
+    match (data_type, value) {
+        (_, ValueRef::Null | ValueRef::Blob(_)) => None,
+        (DataType::Integer, ValueRef::Integer(value)) => Some(Constant::Integer(value)),
+        (DataType::Integer, ValueRef::Real(value)) if value.fract() == 0.0 => Some(Constant::Integer(value as i64)),
+        (DataType::Float, ValueRef::Integer(value)) => NotNan::new(value as f64).ok().map(Constant::Float),
+        (DataType::Float, ValueRef::Real(value)) => NotNan::new(value).ok().map(Constant::Float),
+        (DataType::Symbol, ValueRef::Integer(value)) => Some(Constant::Symbol(value.to_string())),
+        (DataType::Symbol, ValueRef::Real(value)) => Some(Constant::Symbol(value.to_string())),
+        (DataType::Boolean, ValueRef::Integer(value)) => Some(Constant::Boolean(value != 0)),
+        (data_type, ValueRef::Text(text)) => {
+            let text = std::str::from_utf8(text).ok()?;
+            data_type.parse_value(text).and_then(|value| value_to_constant(value, data_type).ok())
+        }
+        _ => None,
+    }
+}
+
+/// a value of the database as written in an error
+fn format_sql_value(value: ValueRef) -> String {
+    match value {
+        ValueRef::Null => "NULL".to_string(),
+        ValueRef::Integer(value) => value.to_string(),
+        ValueRef::Real(value) => value.to_string(),
+        ValueRef::Text(text) => format!("`{}`", String::from_utf8_lossy(text)),
+        ValueRef::Blob(_) => "a blob".to_string(),
+    }
+}
+
+/// a value parsed by [`DataType::parse_value`] as a constant of that type, a NaN is not a float constant
+fn value_to_constant(value: Value, data_type: &DataType) -> Result<Constant, FloatIsNan> {
+    match (data_type, value) {
+        (DataType::Integer, Value::Integer(value)) => Ok(Constant::Integer(value)),
+        (DataType::Float, Value::Real(value)) => NotNan::new(value).map(Constant::Float),
+        (DataType::Symbol, Value::Text(value)) => Ok(Constant::Symbol(value)),
+        (DataType::Boolean, Value::Integer(value)) => Ok(Constant::Boolean(value != 0)),
+        (data_type, value) => unreachable!("{:?} is not parsed as {:?}", value, data_type),
+    }
+}
+
+/// a constant bound as a value of a column, an integer in a float column is bound as a real
+fn constant_to_value(constant: &Constant, data_type: &DataType) -> Value {
+    match constant {
+        Constant::Integer(value) if matches!(data_type, DataType::Float) => Value::Real(*value as f64),
+        Constant::Integer(value) => Value::Integer(*value),
+        Constant::Float(value) => Value::Real(value.into_inner()),
+        Constant::Symbol(value) => Value::Text(value.clone()),
+        Constant::Boolean(value) => Value::Integer(*value as i64),
+    }
+}
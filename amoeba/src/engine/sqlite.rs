@@ -0,0 +1,563 @@
+use super::ast::*;
+use super::analysis::*;
+use super::evaluator::{self, Evaluator, Mode};
+use super::error::RuntimeError;
+use super::runtime::Diff;
+use rusqlite::{Connection, params};
+use log::trace;
+use std::collections::{HashMap, HashSet};
+
+/// [`SqliteEvaluator`] compiles every operation into SQL executed on a SQLite database.
+pub struct SqliteEvaluator<'a> {
+    pub database: &'a Connection,
+    /// the idbs whose tables are created in the on-disk database attached as `disk`
+    pub disk: &'a [String],
+    pub diff: Diff,
+}
+
+impl SqliteEvaluator<'_> {
+    /// the table of a relation, qualified by the schema it is created in.
+    /// once created, a table in the attached database is also found by its unqualified name
+    pub fn table(&self, name: &str) -> String {
+        if self.disk.iter().any(|idb| idb == name) {
+            format!("disk.{}", name)
+        } else {
+            name.to_string()
+        }
+    }
+
+    /// log the statement at the trace level, then execute it
+    pub fn execute(&self, sql: &str) -> Result<(), RuntimeError> {
+        trace!("EXECUTE: {}", sql);
+        self.database.execute(sql, params![])?;
+        Ok(())
+    }
+}
+
+impl Evaluator for SqliteEvaluator<'_> {
+    fn create_relation(&mut self, name: &str, types: &[DataType]) -> Result<(), RuntimeError> {
+        self.execute(&create_table_sql(&self.table(name), types))
+    }
+
+    fn copy_relation(&mut self, from: &str, to: &str) -> Result<(), RuntimeError> {
+        self.execute(&copy_sql(from, to))
+    }
+
+    fn drop_relation(&mut self, name: &str) -> Result<(), RuntimeError> {
+        self.execute(&drop_sql(name))
+    }
+
+    fn select_into(&mut self, rule: &Rule, var_dict: &VarDict, target: &str, mode: Mode) -> Result<(), RuntimeError> {
+        self.execute(&build_rule_sql(rule, var_dict, target, mode)?)
+    }
+
+    fn insert_distinct(&mut self, target: &str, source: &str) -> Result<(), RuntimeError> {
+        self.execute(&union_sql(target, source))
+    }
+
+    fn diff(&mut self, target: &str, left: &str, right: &str, arity: usize) -> Result<(), RuntimeError> {
+        for sql in diff_sql(target, left, right, arity, self.diff) {
+            self.execute(&sql)?;
+        }
+        Ok(())
+    }
+
+    fn count(&mut self, name: &str) -> Result<usize, RuntimeError> {
+        let sql = format!("SELECT COUNT(*) FROM {}", name);
+        let count: i64 = self.database.query_row(&sql, params![], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    fn prepare(&mut self, rule: &Rule, var_dict: &VarDict) -> Result<(), RuntimeError> {
+        for sql in index_sql(rule, var_dict, self.disk) {
+            self.execute(&sql)?;
+        }
+        Ok(())
+    }
+
+    fn begin(&mut self) -> Result<(), RuntimeError> {
+        self.database.execute_batch("BEGIN")?;
+        Ok(())
+    }
+
+    fn commit(&mut self) -> Result<(), RuntimeError> {
+        self.database.execute_batch("COMMIT")?;
+        Ok(())
+    }
+
+    fn rollback(&mut self) -> Result<(), RuntimeError> {
+        if !self.database.is_autocommit() {
+            self.database.execute_batch("ROLLBACK")?;
+        }
+        Ok(())
+    }
+}
+
+/// a table typed by the inferred column types, with a unique constraint on all columns
+pub fn create_table_sql(name: &str, types: &[DataType]) -> String {
+    let columns = types.iter()
+        .enumerate()
+        .map(|(i, data_type)| format!("column_{} {}", i, data_type.sql_type()))
+        .collect::<Vec<String>>();
+    let unique = (0..types.len())
+        .map(|i| format!("column_{}", i))
+        .collect::<Vec<String>>();
+    format!("CREATE TABLE IF NOT EXISTS {} ({}, UNIQUE({}));", name, columns.join(", "), unique.join(", "))
+}
+
+pub fn copy_sql(from: &str, to: &str) -> String {
+    format!("CREATE TABLE {} AS SELECT * FROM {}", to, from)
+}
+
+pub fn drop_sql(name: &str) -> String {
+    format!("DROP TABLE {};", name)
+}
+
+/// insert the tuples of `source` missing from `target`
+pub fn union_sql(target: &str, source: &str) -> String {
+    format!("INSERT OR IGNORE INTO {}\n{}SELECT * FROM {};", target, " ".repeat(9), source)
+}
+
+/// replace the tuples of `target` by those of `left` missing from `right`
+pub fn diff_sql(target: &str, left: &str, right: &str, arity: usize, diff: Diff) -> Vec<String> {
+    let indent = " ".repeat(9);
+    let clear_sql = format!("DELETE FROM {}", target);
+    if let Diff::Except = diff {
+        let insert_sql = format!("INSERT OR IGNORE INTO {}\n{}SELECT * FROM {}\n{}EXCEPT SELECT * FROM {}",
+            target,
+            indent,
+            left,
+            indent,
+            right
+        );
+        return vec![clear_sql, insert_sql];
+    }
+    // use left join
+    let mut insert_sql = format!("INSERT OR IGNORE INTO {}\n{}SELECT {}.* FROM {}\n{}",
+        target,
+        indent,
+        left,
+        left,
+        indent,
+    );
+    let columns = (0..arity).map(|i| format!("column_{}", i)).collect::<Vec<String>>();
+    // LEFT JOIN right ON left.column_0 = right.column_0 AND ...
+    // WHERE right.column_0 IS NULL AND ...
+    insert_sql.push_str(&format!("LEFT JOIN {} ON {}\n",
+        right,
+        columns.iter().map(|column| {
+            format!("{}.{} = {}.{}", left, column, right, column)
+        }).collect::<Vec<String>>().join(" AND "),
+    ));
+    insert_sql.push_str(&format!("{}WHERE {}",
+        indent,
+        columns.iter().map(|column| {
+            format!("{}.{} IS NULL", right, column)
+        }).collect::<Vec<String>>().join(" AND "),
+    ));
+    vec![clear_sql, insert_sql]
+}
+
+/// select the head tuples of a rule from the relations in its body into `target`.
+/// base cases and iterations of recursive rules share this statement,
+/// and only differ in the atom reading a delta table, as decided by the mode
+pub fn build_rule_sql(rule: &Rule, var_dict: &VarDict, target: &str, mode: Mode) -> Result<String, RuntimeError> {
+    let target = Atom { predicate: target.to_string(), ..rule.head.clone() };
+    Ok(format!("INSERT OR IGNORE INTO {}\n{}", target.insert_target(), select_rule_sql(rule, var_dict, mode, false)?))
+}
+
+/// select the head tuples of a rule with the number of their derivations as a last column `multiplicity`.
+/// every match of the body is a derivation, so that a guard is joined rather than semi-joined,
+/// and a group of an aggregating rule is derived once
+pub fn bag_sql(rule: &Rule) -> Result<String, RuntimeError> {
+    select_rule_sql(rule, &VarDict::new(rule), Mode::Base, true)
+}
+
+/// add up the multiplicities of the tuples selected by several [`bag_sql`] statements
+pub fn sum_bags_sql(bags: &[String], arity: usize) -> String {
+    let indent = " ".repeat(9);
+    let columns = (0..arity).map(|i| format!("column_{}", i)).collect::<Vec<String>>();
+    format!("SELECT {}, SUM(multiplicity) AS multiplicity FROM (\n{}\n{})\n{}GROUP BY {}",
+        columns.join(", "),
+        bags.join(&format!("\n{}UNION ALL\n", indent)),
+        indent,
+        indent,
+        columns.join(", ")
+    )
+}
+
+fn select_rule_sql(rule: &Rule, var_dict: &VarDict, mode: Mode, bag: bool) -> Result<String, RuntimeError> {
+    let indent = " ".repeat(9);
+    let mut sql = String::new();
+    let mut select_sql = Vec::new();
+    let mut where_sql = Vec::new();
+    // push select_sql stmts
+    // head terms which are not aggregated are the grouping keys
+    let mut group_sql = Vec::new();
+    let mut aggregate = false;
+    for (index, term) in rule.head.terms.iter().enumerate() {
+        let var = term.is_nontrivial_variable();
+        let column = match (var.as_ref().and_then(|var| var_dict.binding(var)), term) {
+            (Some((clause_index, term_index)), _) => format!("{}.column_{}", alias(clause_index), term_index),
+            (None, Term::Constant(constant)) => constant.to_sql(),
+            // a variable assigned by arithmetic is a computed column
+            (None, _) => match var.as_ref().and_then(|var| var_dict.assignments.get(var)) {
+                Some((_, expr)) if var_dict.is_aggregated(expr) => {
+                    aggregate = true;
+                    select_sql.push(format!("{} AS column_{}", assigned_sql(rule, expr, var_dict)?, index));
+                    continue;
+                }
+                // a head term dividing by zero is NULL, and the tuple is not derived
+                Some((_, expr)) if expr.has_division() => {
+                    where_sql.push(format!("{} IS NOT NULL", arith_to_sql(rule, expr, var_dict)?));
+                    assigned_sql(rule, expr, var_dict)?
+                }
+                Some((_, expr)) => assigned_sql(rule, expr, var_dict)?,
+                None => return Err(RuntimeError::UnboundHeadVariable {
+                    predicate: rule.head.predicate.clone(),
+                    var: term.to_string(),
+                }),
+            },
+        };
+        // a constant is the same in every group, and an integer in GROUP BY would name a result column
+        if !matches!(term, Term::Constant(_)) {
+            group_sql.push(column.clone());
+        }
+        select_sql.push(format!("{} AS column_{}", column, index));
+    }
+    // an aggregating rule folds every body tuple, so that no atom of it is a semi-join
+    let (from_sql, mut body_where_sql) = body_sql(rule, var_dict, |clause_index, atom| {
+        mode.relation(clause_index, &atom.predicate)
+    }, !bag && !rule.has_aggregate())?;
+    where_sql.append(&mut body_where_sql);
+    // a condition on an aggregate filters the groups rather than the body tuples
+    let mut having_sql = Vec::new();
+    for (clause_index, clause) in rule.body.iter().enumerate() {
+        if let Clause::Arithmetic(arith) = clause {
+            if !var_dict.is_assignment(clause_index) && var_dict.is_aggregated(arith) {
+                aggregate = true;
+                having_sql.push(arith_to_sql(rule, arith, var_dict)?);
+            }
+        }
+    }
+    if bag {
+        let multiplicity = if aggregate { "1" } else { "COUNT(*)" };
+        select_sql.push(format!("{} AS multiplicity", multiplicity));
+        sql.push_str(&format!("{}SELECT {}\n", indent, select_sql.join(", ")));
+    } else {
+        // body variables missing from the head are existential,
+        // so distinct bodies may project onto the same head tuple
+        sql.push_str(&format!("{}SELECT DISTINCT {}\n", indent, select_sql.join(", ")));
+    }
+    sql.push_str(&from_sql.join("\n"));
+    if !where_sql.is_empty() {
+        sql.push_str(&format!("\n{}WHERE {}", indent, where_sql.join(" AND ")));
+    }
+    if (aggregate || bag) && !group_sql.is_empty() {
+        sql.push_str(&format!("\n{}GROUP BY {}", indent, group_sql.join(", ")));
+    } else if bag && !aggregate {
+        // a single group is counted even if the body has no match
+        having_sql.push("COUNT(*) > 0".to_string());
+    }
+    if !having_sql.is_empty() {
+        sql.push_str(&format!("\n{}HAVING {}", indent, having_sql.join(" AND ")));
+    }
+    Ok(sql)
+}
+
+/// select the tuples matching the non-negated atoms in the body of a rule,
+/// for a single instantiation of the rule deriving the head `tuple`.
+/// the atoms read the relations given by `relation`, e.g. the tuples derived before some iteration
+pub fn derivation_sql(
+    rule: &Rule,
+    tuple: &[Constant],
+    relation: impl Fn(usize, &Atom) -> String
+) -> Result<String, RuntimeError> {
+    let indent = " ".repeat(9);
+    let var_dict = VarDict::new(rule);
+    // every atom is joined, so that the tuple matching each of them is selected
+    let (from_sql, mut where_sql) = body_sql(rule, &var_dict, relation, false)?;
+    let mut select_sql = Vec::new();
+    rule.body.iter().enumerate().for_each(|(clause_index, clause)| {
+        if let Clause::Atom(atom) = clause {
+            if !atom.negation {
+                (0..atom.terms.len()).for_each(|term_index| {
+                    select_sql.push(format!("{}.column_{}", alias(clause_index), term_index));
+                });
+            }
+        }
+    });
+    // every head term equals the value of the tuple at its position
+    for (term, value) in rule.head.terms.iter().zip(tuple) {
+        let var = term.is_nontrivial_variable();
+        let column = match var.as_ref().and_then(|var| var_dict.binding(var)) {
+            Some((clause_index, term_index)) => format!("{}.column_{}", alias(clause_index), term_index),
+            None => match (term, var.as_ref().and_then(|var| var_dict.assignments.get(var))) {
+                (Term::Constant(constant), _) => constant.to_sql(),
+                (_, Some((_, expr))) => assigned_sql(rule, expr, &var_dict)?,
+                (_, None) => return Err(RuntimeError::UnboundHeadVariable {
+                    predicate: rule.head.predicate.clone(),
+                    var: term.to_string(),
+                }),
+            },
+        };
+        where_sql.push(format!("{} = {}", column, value.to_sql()));
+    }
+    let mut sql = format!("SELECT {}\n", select_sql.join(", "));
+    sql.push_str(&from_sql.join("\n"));
+    if !where_sql.is_empty() {
+        sql.push_str(&format!("\n{}WHERE {}", indent, where_sql.join(" AND ")));
+    }
+    sql.push_str(&format!("\n{}LIMIT 1", indent));
+    Ok(sql)
+}
+
+/// the FROM and WHERE clauses of the body of a rule, every atom is aliased by its position in body,
+/// and reads the relation given by `relation`.
+/// under `semi_join`, a guard is an `EXISTS` subquery, and none of its columns can be selected
+fn body_sql(
+    rule: &Rule,
+    var_dict: &VarDict,
+    relation: impl Fn(usize, &Atom) -> String,
+    semi_join: bool
+) -> Result<(Vec<String>, Vec<String>), RuntimeError> {
+    let indent = " ".repeat(9);
+    let mut from_sql = Vec::new();
+    let mut where_sql = Vec::new();
+    // a guard is a semi-join rather than a join, unless every atom is a guard
+    let atoms = rule.body.iter()
+        .enumerate()
+        .filter_map(|(clause_index, clause)| match clause {
+            Clause::Atom(atom) if !atom.negation => Some((clause_index, atom)),
+            _ => None,
+        })
+        .collect::<Vec<(usize, &Atom)>>();
+    let mut guards = atoms.iter()
+        .filter(|(clause_index, atom)| var_dict.is_guard(*clause_index, atom))
+        .map(|(clause_index, _)| *clause_index)
+        .collect::<HashSet<usize>>();
+    if !semi_join {
+        guards.clear();
+    }
+    if guards.len() == atoms.len() {
+        if let Some((clause_index, _)) = atoms.first() {
+            guards.remove(clause_index);
+        }
+    }
+    rule.body.iter().enumerate().for_each(|(clause_index, clause)| {
+        if let Clause::Atom(atom) = clause {
+            if atom.negation {
+                where_sql.push(format!("NOT {}", exists_sql(clause_index, atom, &atom.predicate, var_dict)));
+                return;
+            }
+            if guards.contains(&clause_index) {
+                where_sql.push(exists_sql(clause_index, atom, &relation(clause_index, atom), var_dict));
+                return;
+            }
+            let mut join_sql = Vec::new();
+            atom.terms.iter().enumerate().for_each(|(term_index, term)| {
+                match term {
+                    Term::Constant(constant) => {
+                        let stmt = format!("{}.column_{} = {}",
+                            alias(clause_index),
+                            term_index,
+                            constant.to_sql());
+                        where_sql.push(stmt);
+                    }
+                    Term::Variable(_) => {
+                        if let Some(var) = term.is_nontrivial_variable() {
+                            let binding = var_dict.binding(&var)
+                                .expect("Variable should be bound in body");
+                            if binding != (clause_index, term_index) {
+                                let stmt = format!("{}.column_{} = {}.column_{}",
+                                    alias(binding.0),
+                                    binding.1,
+                                    alias(clause_index),
+                                    term_index);
+                                join_sql.push(stmt);
+                            }
+                        }
+                    }
+                }
+            });
+            let table = format!("{} AS {}", relation(clause_index, atom), alias(clause_index));
+            if from_sql.is_empty() {
+                // the first atom has no join condition
+                where_sql.append(&mut join_sql);
+                from_sql.push(format!("{}FROM {}", indent, table));
+            } else if join_sql.is_empty() {
+                from_sql.push(format!("{}JOIN {}", indent, table));
+            } else {
+                from_sql.push(format!("{}JOIN {} ON {}", indent, table, join_sql.join(" AND ")));
+            }
+        }
+    });
+    // push arithmetic where_sql stmts, assignments are inlined where their variable is used,
+    // and a condition on an aggregate is left to the HAVING clause of the rule
+    for (clause_index, clause) in rule.body.iter().enumerate() {
+        if let Clause::Arithmetic(arith) = clause {
+            if !var_dict.is_assignment(clause_index) && !var_dict.is_aggregated(arith) {
+                where_sql.push(arith_to_sql(rule, arith, var_dict)?);
+            }
+        }
+    }
+    Ok((from_sql, where_sql))
+}
+
+/// index every column a variable is joined on, once before the fixpoint loop.
+/// recursive atoms read from the delta table, so both it and the original are indexed.
+/// the index of a table in the attached on-disk database is created in that database
+pub fn index_sql(rule: &Rule, var_dict: &VarDict, disk: &[String]) -> Vec<String> {
+    let mut statements = Vec::new();
+    let mut columns = Vec::new();
+    for groups in var_dict.clause_dict.values() {
+        let groups = groups.iter()
+            .filter(|group| !group.is_arith)
+            .collect::<Vec<&VarGroup>>();
+        let occurrences = groups.iter()
+            .map(|group| group.term_indexes.len())
+            .sum::<usize>();
+        // a variable occurring once is not a join key
+        if occurrences < 2 {
+            continue;
+        }
+        for group in groups {
+            let predicate = match &rule.body[group.clause_index] {
+                Clause::Atom(atom) => atom.predicate.clone(),
+                Clause::Arithmetic(_) => unreachable!(),
+            };
+            for term_index in group.term_indexes.iter() {
+                if predicate == rule.head.predicate {
+                    columns.push((evaluator::delta_relation(&predicate), *term_index));
+                }
+                columns.push((predicate.clone(), *term_index));
+            }
+        }
+    }
+    columns.sort();
+    columns.dedup();
+    for (table, column) in columns {
+        let schema = if disk.contains(&table) { "disk." } else { "" };
+        let sql = format!("CREATE INDEX IF NOT EXISTS {}index_{}_{} ON {}(column_{})",
+            schema,
+            table,
+            column,
+            table,
+            column
+        );
+        statements.push(sql);
+    }
+    statements
+}
+
+/// body atoms are aliased by their position, so that
+/// a predicate can appear more than once in a rule body
+fn alias(clause_index: usize) -> String {
+    format!("b{}", clause_index)
+}
+
+/// lower an arithmetic subgoal into a SQL expression,
+/// variables are resolved to the column binding them in the body
+/// the value of an assigned expression, a condition, e.g. `B = (X > 10)`, is a boolean column of 0 or 1,
+/// and is 0 rather than NULL when an operand is NULL
+fn assigned_sql(rule: &Rule, expr: &Arith, var_dict: &VarDict) -> Result<String, RuntimeError> {
+    let sql = arith_to_sql(rule, expr, var_dict)?;
+    if !expr.is_condition() {
+        return Ok(sql);
+    }
+    Ok(format!("CASE WHEN {} THEN 1 ELSE 0 END", sql))
+}
+
+fn arith_to_sql(rule: &Rule, arith: &Arith, var_dict: &VarDict) -> Result<String, RuntimeError> {
+    let operand = |operand: &Option<Box<Arith>>| {
+        let operand = operand.as_ref().expect("Operand should be present");
+        arith_to_sql(rule, operand, var_dict)
+    };
+    let operator = match &arith.operator {
+        Operator::Leaf(Term::Constant(constant)) => return Ok(constant.to_sql()),
+        Operator::Leaf(term) => {
+            let var = term.is_nontrivial_variable();
+            if let Some((clause_index, term_index)) = var.as_ref().and_then(|var| var_dict.binding(var)) {
+                return Ok(format!("{}.column_{}", alias(clause_index), term_index));
+            }
+            return match var.as_ref().and_then(|var| var_dict.assignments.get(var)) {
+                Some((_, expr)) => Ok(format!("({})", assigned_sql(rule, expr, var_dict)?)),
+                None => Err(RuntimeError::UnboundVariable {
+                    predicate: rule.head.predicate.clone(),
+                    var: term.to_string(),
+                }),
+            };
+        }
+        Operator::Aggregate(Aggregate::Count) => return Ok("COUNT(*)".to_string()),
+        Operator::Aggregate(Aggregate::CountDistinct) => return Ok(format!("COUNT(DISTINCT {})", operand(&arith.rhs)?)),
+        Operator::Aggregate(aggregate) => {
+            let aggregate = aggregate.to_string().to_uppercase();
+            return Ok(format!("{}({})", aggregate, operand(&arith.rhs)?));
+        }
+        Operator::Function(Function::Abs) => return Ok(format!("ABS({})", operand(&arith.rhs)?)),
+        // the scalar MIN and MAX of SQLite, rather than the aggregates, as they are given two operands
+        Operator::Function(function) => {
+            let function = function.to_string().to_uppercase();
+            return Ok(format!("{}({}, {})", function, operand(&arith.lhs)?, operand(&arith.rhs)?));
+        }
+        Operator::Neg => return Ok(format!("(NOT {})", operand(&arith.rhs)?)),
+        Operator::Sub if arith.lhs.is_none() => return Ok(format!("(-{})", operand(&arith.rhs)?)),
+        Operator::In(constants) => {
+            let constants = constants.iter().map(Constant::to_sql).collect::<Vec<String>>();
+            return Ok(format!("({} IN ({}))", operand(&arith.lhs)?, constants.join(", ")));
+        }
+        // a zero divisor only known at runtime gives NULL, which fails every filter
+        Operator::Div | Operator::Mod => {
+            let divisor = arith.rhs.as_ref().expect("Operand should be present");
+            if let Operator::Leaf(Term::Constant(constant)) = &divisor.operator {
+                if constant.is_zero() {
+                    return Err(RuntimeError::DivisionByZero(rule.head.predicate.clone()));
+                }
+            }
+            let operator = if let Operator::Mod = arith.operator { "%" } else { "/" };
+            return Ok(format!("({} {} NULLIF({}, 0))", operand(&arith.lhs)?, operator, operand(&arith.rhs)?));
+        }
+        // symbols are compared lexicographically, by the binary collation of their text
+        Operator::Unifier => "=",
+        Operator::Disunifier => "<>",
+        Operator::Less => "<",
+        Operator::LessEqual => "<=",
+        Operator::Greater => ">",
+        Operator::GreaterEqual => ">=",
+        Operator::And => "AND",
+        Operator::Or => "OR",
+        Operator::Add => "+",
+        Operator::Sub => "-",
+        Operator::Mul => "*",
+    };
+    Ok(format!("({} {} {})", operand(&arith.lhs)?, operator, operand(&arith.rhs)?))
+}
+
+/// lower an atom into an `EXISTS` subquery on `table`, correlated with the columns binding its variables
+/// in the body, a negated subgoal is `NOT EXISTS`, and a guard is a semi-join
+fn exists_sql(clause_index: usize, atom: &Atom, table: &str, var_dict: &VarDict) -> String {
+    let mut where_sql = Vec::new();
+    let mut local_bindings = HashMap::new();
+    atom.terms.iter().enumerate().for_each(|(term_index, term)| {
+        let column = format!("{}.column_{}", alias(clause_index), term_index);
+        if let Term::Constant(constant) = term {
+            where_sql.push(format!("{} = {}", column, constant.to_sql()));
+        }
+        if let Some(var) = term.is_nontrivial_variable() {
+            // variables only occurring in the negated subgoal are bound locally
+            let binding = match var_dict.binding(&var) {
+                Some((clause_index, term_index)) => format!("{}.column_{}", alias(clause_index), term_index),
+                None => local_bindings.entry(var).or_insert(column.clone()).clone(),
+            };
+            if binding != column {
+                where_sql.push(format!("{} = {}", binding, column));
+            }
+        }
+    });
+    let mut sql = format!("EXISTS (SELECT 1 FROM {} AS {}", table, alias(clause_index));
+    if !where_sql.is_empty() {
+        sql.push_str(&format!(" WHERE {}", where_sql.join(" AND ")));
+    }
+    sql.push(')');
+    sql
+}
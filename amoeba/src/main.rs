@@ -1,6 +1,8 @@
-mod syntax;
-mod engine;
+use amoeba::engine::{self, Options, Writeback, Format, Backend, Journal, Diff, Strategy};
 use clap::Parser;
+use colored::Colorize;
+use log::{LevelFilter, Log, Metadata, Record};
+use ordered_float::NotNan;
 use std::time::Instant;
 
 #[derive(Parser, Debug)]
@@ -8,16 +10,219 @@ use std::time::Instant;
 struct Args {
     #[arg(long)]
     source: String,
+    /// the database of the edbs, by default the source with the extension .db
+    #[arg(long, value_name = "PATH")]
+    db: Option<String>,
+    /// print every statement executed and every iteration of the evaluation
     #[arg(long, default_value = "false")]
     verbose: bool,
     #[arg(long, default_value = "false")]
     bench: bool,
+    /// leave the source database untouched
+    #[arg(long, default_value = "false")]
+    no_writeback: bool,
+    /// only write the tables of @output predicates back to the source database
+    #[arg(long, default_value = "false")]
+    writeback_outputs: bool,
+    /// write every materialized idb back to the source database as the table idb_<predicate>,
+    /// leaving the tables of edbs untouched
+    #[arg(long, default_value = "false")]
+    writeback_idbs: bool,
+    /// write the predicate dependency graph to a Graphviz DOT file
+    #[arg(long)]
+    dump_graph: Option<String>,
+    /// print the strata of the program in evaluation order, and which predicates are recursive
+    #[arg(long, default_value = "false")]
+    show_strata: bool,
+    /// abort a recursive predicate which has not reached a fixpoint after N iterations
+    #[arg(long, value_name = "N")]
+    max_iterations: Option<usize>,
+    /// print the generated SQL without evaluating the program
+    #[arg(long, default_value = "false")]
+    explain: bool,
+    /// report every problem of the program and its database, without evaluating the program,
+    /// and exit with status 1 if there is any
+    #[arg(long, default_value = "false")]
+    check: bool,
+    /// write query results as a preview, or as typed json
+    #[arg(long, value_name = "FORMAT", default_value = "preview", value_parser = ["preview", "json"])]
+    format: String,
+    /// print the first and last N tuples of a query with more than 2N tuples, or only the count if N is 0
+    #[arg(long, value_name = "N", default_value = "10")]
+    preview: usize,
+    /// rewrite the program with magic sets, so that queries with constants only derive relevant tuples
+    #[arg(long, default_value = "false")]
+    magic: bool,
+    /// evaluate rules with SQLite, or with tuples held in memory for small programs without arithmetic
+    #[arg(long, value_name = "BACKEND", default_value = "sqlite", value_parser = ["sqlite", "memory"])]
+    backend: String,
+    /// print the iterations and delta sizes of every recursive predicate
+    #[arg(long, default_value = "false")]
+    stats: bool,
+    /// evaluate the strata which do not depend on each other in parallel threads
+    #[arg(long, default_value = "false")]
+    parallel: bool,
+    /// print the number and cumulative time of the statements of the evaluation, by kind
+    #[arg(long, default_value = "false")]
+    profile_sql: bool,
+    /// print the plans SQLite chooses for an iteration of every recursive rule, after evaluating the program
+    #[arg(long, default_value = "false")]
+    query_plan: bool,
+    /// only evaluate and write the @output predicate NAME, and the idbs it depends on
+    #[arg(long, value_name = "NAME")]
+    query: Option<String>,
+    /// print a derivation of a tuple, written as PREDICATE,VALUE,..., e.g. reachable,a,d
+    #[arg(long, value_name = "TUPLE")]
+    trace: Option<String>,
+    /// print the tuples of an evaluated predicate matching an atom, e.g. 'reachable(a, Y)', or 'edge(a, _)'
+    /// where `_` matches any value
+    #[arg(long, value_name = "ATOM")]
+    ask: Option<String>,
+    /// print queries ordered by their columns, queries written to files are always ordered
+    #[arg(long, default_value = "false")]
+    sorted: bool,
+    /// print and write at most N tuples of each query, after ordering them under --sorted
+    #[arg(long, value_name = "N")]
+    limit: Option<usize>,
+    /// compare floats with == up to a tolerance EPS, rather than exactly
+    #[arg(long, value_name = "EPS", value_parser = parse_epsilon)]
+    epsilon: Option<NotNan<f64>>,
+    /// warn about the tuples held more than once by an edb, after loading the edbs
+    #[arg(long, default_value = "false")]
+    warn_duplicate_edb: bool,
+    /// the page cache of the database evaluating the program, in megabytes
+    #[arg(long, value_name = "MB")]
+    cache_mb: Option<usize>,
+    /// keep the rollback journal in memory, or no journal at all for speed
+    #[arg(long, value_name = "MODE", default_value = "memory", value_parser = ["memory", "off"])]
+    journal_mode: String,
+    /// keep the tuples newly derived by an iteration with a left join on every column, or with EXCEPT
+    #[arg(long, value_name = "STRATEGY", default_value = "anti-join", value_parser = ["anti-join", "except"])]
+    diff: String,
+    /// derive an iteration of a recursive rule from the tuples new in the previous one, or from every tuple
+    #[arg(long, value_name = "STRATEGY", default_value = "semi-naive", value_parser = ["semi-naive", "naive"])]
+    strategy: String,
+    /// store the table of this idb in an on-disk database beside the database of the edbs,
+    /// rather than in memory, may be repeated
+    #[arg(long, value_name = "NAME")]
+    disk: Vec<String>,
+}
+
+/// the logger of --verbose, which prints the records of the engine as `LABEL: message`,
+/// the number of an iteration is yellow, or green once the fixpoint is reached
+struct VerboseLogger;
+
+static LOGGER: VerboseLogger = VerboseLogger;
+
+impl Log for VerboseLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.target().starts_with("amoeba")
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let message = record.args().to_string();
+        match message.split_once(": ") {
+            // the iteration follows the predicate, e.g. `reachable(2)`
+            Some((label @ ("ITERATE" | "COMMIT" | "FIXPOINT"), iteration)) => {
+                let (head, counter) = iteration.rsplit_once('(').unwrap_or((iteration, ""));
+                let counter = counter.trim_end_matches(')');
+                let counter = if label == "FIXPOINT" { counter.green() } else { counter.yellow() };
+                println!("{}: {}({})", label.yellow(), head, counter);
+            }
+            Some((label, rest)) => println!("{}: {}", label.green(), rest),
+            None => println!("{}", message),
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// a tolerance is a finite, non-negative float
+fn parse_epsilon(value: &str) -> Result<NotNan<f64>, String> {
+    value.parse::<f64>().ok()
+        .filter(|epsilon| epsilon.is_finite() && *epsilon >= 0.0)
+        .and_then(|epsilon| NotNan::new(epsilon).ok())
+        .ok_or_else(|| format!("`{}` is not a finite, non-negative float", value))
 }
 
 fn main() {
     let cli = Args::parse();
+    if cli.verbose {
+        log::set_logger(&LOGGER).expect("Logger should only be set once");
+        log::set_max_level(LevelFilter::Trace);
+    }
+    if let Some(graph_path) = &cli.dump_graph {
+        engine::dump_graph(&cli.source[..], graph_path);
+    }
+    if cli.show_strata {
+        engine::show_strata(&cli.source[..]);
+    }
     let now = Instant::now();
-    engine::run(&cli.source[..], cli.verbose);
+    let writeback = if cli.no_writeback {
+        Writeback::Disabled
+    } else if cli.writeback_outputs {
+        Writeback::Outputs
+    } else if cli.writeback_idbs {
+        Writeback::Idbs
+    } else {
+        Writeback::All
+    };
+    let format = match cli.format.as_str() {
+        "json" => Format::Json,
+        _ => Format::Preview,
+    };
+    let backend = match cli.backend.as_str() {
+        "memory" => Backend::Memory,
+        _ => Backend::Sqlite,
+    };
+    let journal = match cli.journal_mode.as_str() {
+        "off" => Journal::Off,
+        _ => Journal::Memory,
+    };
+    let diff = match cli.diff.as_str() {
+        "except" => Diff::Except,
+        _ => Diff::AntiJoin,
+    };
+    let strategy = match cli.strategy.as_str() {
+        "naive" => Strategy::Naive,
+        _ => Strategy::SemiNaive,
+    };
+    let options = Options {
+        writeback,
+        max_iterations: cli.max_iterations,
+        explain: cli.explain,
+        format,
+        preview: cli.preview,
+        magic: cli.magic,
+        backend,
+        stats: cli.stats,
+        profile_sql: cli.profile_sql,
+        query_plan: cli.query_plan,
+        parallel: cli.parallel,
+        query: cli.query,
+        trace: cli.trace,
+        ask: cli.ask,
+        sorted: cli.sorted,
+        limit: cli.limit,
+        epsilon: cli.epsilon,
+        warn_duplicate_edb: cli.warn_duplicate_edb,
+        cache_mb: cli.cache_mb,
+        journal,
+        diff,
+        strategy,
+        disk: cli.disk,
+    };
+    if cli.check {
+        // a problem fails the check, so that it may gate a script
+        if !engine::check(&cli.source[..], cli.db.as_deref(), options) {
+            std::process::exit(1);
+        }
+    } else {
+        engine::run(&cli.source[..], cli.db.as_deref(), options);
+    }
     let elapsed = now.elapsed();
     if cli.bench {
         println!("{}.{:03}s", elapsed.as_secs(), elapsed.subsec_millis());
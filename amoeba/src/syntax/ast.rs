@@ -3,6 +3,7 @@ use std::collections::HashSet;
 use std::fmt::Display;
 
 /// [`Rule`] can either be an edb or idb or query.
+/// a rule without body is a fact, e.g. edge(a, b).
 /// a Datalog program is a set of rules
 pub type Program = Vec<Rule>;
 #[derive(Debug, Clone)]
@@ -10,6 +11,12 @@ pub struct Rule {
     pub io: IO,
     pub head: Atom,
     pub body: Vec<Clause>,
+    /// the names of the columns of an edb declaration, e.g. edge(src: sym, dst: sym),
+    /// empty if no column is named
+    pub columns: Vec<Option<String>>,
+    /// the bodies after the first of a disjunctive body, e.g. `b(X)` in `p(X) :- a(X) ; b(X)`,
+    /// each of them becomes a rule of its own in a context, where this is always empty
+    pub disjuncts: Vec<Vec<Clause>>,
 }
 
 impl Rule {
@@ -57,15 +64,29 @@ impl Rule {
         });
     }
 
-    pub fn is_base_case(&self, predicates: &Vec<String>) -> bool {
-        // body only contains edb
+    pub fn is_base_case(&self, predicates: &[String]) -> bool {
+        // body only contains edb, arithmetic subgoals are filters
         self.body.iter().all(|clause| {
             match clause {
                 Clause::Atom(atom) => predicates.contains(&atom.predicate),
-                Clause::Arithmetic(_) => false,
+                Clause::Arithmetic(_) => true,
             }
         })
     }
+
+    pub fn is_fact(&self) -> bool {
+        matches!(self.io, IO::Silent) && self.body.is_empty()
+    }
+
+    pub fn is_bag(&self) -> bool {
+        matches!(self.io, IO::WriteBag(_))
+    }
+
+    pub fn has_aggregate(&self) -> bool {
+        self.body.iter().any(|clause| {
+            matches!(clause, Clause::Arithmetic(arith) if arith.is_aggregate())
+        })
+    }
 }
 
 /// [`Clause`] is an atom or a arithmetic expression.
@@ -77,20 +98,11 @@ pub enum Clause {
     Arithmetic(Arith),
 }
 
-impl Clause {
-    pub fn to_string(&self) -> String {
-        match self {
-            Clause::Atom(atom) => atom.predicate.clone(),
-            Clause::Arithmetic(_) => String::from("arith")
-        }
-    }
-}
-
 impl Display for Clause {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Clause::Atom(atom) => write!(f, "{}", atom.to_string()),
-            Clause::Arithmetic(_) => write!(f, "arith")
+            Clause::Atom(atom) => write!(f, "{}", atom),
+            Clause::Arithmetic(arith) => write!(f, "{}", arith)
         }
     }
 }
@@ -100,13 +112,48 @@ impl Display for Clause {
 /// @input(file) reads file.csv as input to edb.
 /// @output(file) writes output of query to file.csv.
 /// @output() writes output of query to stdout.
+/// @output_bag is @output under bag semantics, each tuple is given with its number of derivations.
+/// bare @input and @output are the same as @input() and @output().
+///
+/// ```
+/// use amoeba::syntax::{parse, ast::IO};
+///
+/// let context = parse("
+///     @input( edges )
+///     edge(sym, sym)
+///     @input
+///     node(sym)
+///     @input( )
+///     weight(sym, int)
+///     @output(  result)
+///     edge(X, Y)
+///     @output
+///     node(X)
+/// ").unwrap();
+/// assert!(matches!(&context.edbs["edge"].io, IO::Read(Some(file)) if file == "edges"));
+/// assert!(matches!(context.edbs["node"].io, IO::Read(None)));
+/// assert!(matches!(context.edbs["weight"].io, IO::Read(None)));
+/// assert!(matches!(&context.queries["edge"][0].io, IO::Write(Some(file)) if file == "result"));
+/// assert!(matches!(context.queries["node"][0].io, IO::Write(None)));
+/// ```
 #[derive(Debug, Clone)]
 pub enum IO {
     Read(Option<String>),
     Write(Option<String>),
+    WriteBag(Option<String>),
     Silent
 }
 
+impl IO {
+    /// the file an @output or @output_bag query is written to
+    pub fn output_file(&self) -> Option<&String> {
+        match self {
+            IO::Write(file) | IO::WriteBag(file) => file.as_ref(),
+            _ => None,
+        }
+    }
+}
+
 /// [`Atom`] is a predicate with terms.
 /// path(X, b) is a predicate with terms X and b.
 #[derive(Debug, Clone)]
@@ -117,12 +164,13 @@ pub struct Atom {
 }
 
 impl Atom {
-    pub fn to_string(&self) -> String {
+    /// `path(column_0, column_1)`, the column list used as an insertion target
+    pub fn insert_target(&self) -> String {
         let mut string = String::new();
         string.push_str(&self.predicate);
         string.push('(');
-        for (i, _) in self.terms.iter().enumerate() {
-            let term_string = String::from(format!("column_{}", i));
+        for i in 0..self.terms.len() {
+            let term_string = format!("column_{}", i);
             string.push_str(&term_string);
             if i != self.terms.len() - 1 {
                 string.push_str(", ");
@@ -186,7 +234,9 @@ impl Display for Term {
 /// path(X, Y) has variables X and Y.
 /// variable appears in head of a idb predicate is distinguished.
 /// variable appears in body of a idb predicate is undistinguished.
-/// underscore(_) represents a free variable.
+/// underscore(_) represents a free variable, a don't-care matching any value.
+/// every underscore is distinct, so edge(X, _), edge(_, X) never joins the two underscores,
+/// and a free variable is not allowed in the head of an idb.
 /// variable should be capitalized.
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub enum Variable {
@@ -207,7 +257,8 @@ impl Display for Variable {
 
 /// [`Constant`] represents a constant value of a term.
 /// edge(a, b) has constant value a and b, with type `Constant::Symbol`.
-/// constant value should be lowercase.
+/// an unquoted symbol should be lowercase, any other symbol is quoted, e.g. "New York".
+/// boolean is written as `true` or `false` and stored as 1 or 0.
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub enum Constant {
     Integer(i64),
@@ -216,13 +267,151 @@ pub enum Constant {
     Boolean(bool),
 }
 
-// impl Display for constant
+impl Constant {
+    /// the constant as a SQL literal, a boolean is written as 1 or 0 as it is stored,
+    /// and a quote inside a symbol is doubled
+    pub fn to_sql(&self) -> String {
+        match self {
+            Constant::Symbol(value) => format!("'{}'", value.replace('\'', "''")),
+            Constant::Boolean(value) => (*value as i64).to_string(),
+            constant => constant.to_string(),
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Constant::Integer(value) => *value == 0,
+            Constant::Float(value) => value.into_inner() == 0.0,
+            _ => false,
+        }
+    }
+
+    /// the value of an integer, none for any other constant
+    ///
+    /// ```
+    /// use amoeba::syntax::ast::Constant;
+    ///
+    /// assert_eq!(Constant::Integer(42).as_i64(), Some(42));
+    /// assert_eq!(Constant::Symbol("a".to_string()).as_i64(), None);
+    /// assert_eq!(Constant::Boolean(true).as_i64(), None);
+    /// ```
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Constant::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// the value of a number, an integer is converted to a float, none for any other constant
+    ///
+    /// ```
+    /// use amoeba::syntax::ast::Constant;
+    /// use ordered_float::NotNan;
+    ///
+    /// assert_eq!(Constant::Float(NotNan::new(1.5).unwrap()).as_f64(), Some(1.5));
+    /// assert_eq!(Constant::Integer(2).as_f64(), Some(2.0));
+    /// assert_eq!(Constant::Symbol("a".to_string()).as_f64(), None);
+    /// ```
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Constant::Integer(value) => Some(*value as f64),
+            Constant::Float(value) => Some(value.into_inner()),
+            _ => None,
+        }
+    }
+
+    /// the value of a symbol, none for any other constant
+    ///
+    /// ```
+    /// use amoeba::syntax::ast::Constant;
+    ///
+    /// assert_eq!(Constant::Symbol("New York".to_string()).as_str(), Some("New York"));
+    /// assert_eq!(Constant::Integer(1).as_str(), None);
+    /// ```
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Constant::Symbol(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// the value of a boolean, none for any other constant, even the integers 1 and 0
+    ///
+    /// ```
+    /// use amoeba::syntax::ast::Constant;
+    ///
+    /// assert_eq!(Constant::Boolean(false).as_bool(), Some(false));
+    /// assert_eq!(Constant::Integer(1).as_bool(), None);
+    /// ```
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Constant::Boolean(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+/// a constant is converted into a value of the matching type, see the accessors of [`Constant`],
+/// and is given back if the types do not match
+///
+/// ```
+/// use amoeba::syntax::ast::Constant;
+///
+/// assert_eq!(i64::try_from(Constant::Integer(7)), Ok(7));
+/// assert_eq!(f64::try_from(Constant::Integer(7)), Ok(7.0));
+/// assert_eq!(String::try_from(Constant::Symbol("a".to_string())), Ok("a".to_string()));
+/// assert_eq!(bool::try_from(Constant::Boolean(true)), Ok(true));
+/// assert_eq!(i64::try_from(Constant::Boolean(true)), Err(Constant::Boolean(true)));
+/// assert_eq!(String::try_from(Constant::Integer(7)), Err(Constant::Integer(7)));
+/// ```
+impl TryFrom<Constant> for i64 {
+    type Error = Constant;
+
+    fn try_from(constant: Constant) -> Result<Self, Self::Error> {
+        constant.as_i64().ok_or(constant)
+    }
+}
+
+impl TryFrom<Constant> for f64 {
+    type Error = Constant;
+
+    fn try_from(constant: Constant) -> Result<Self, Self::Error> {
+        constant.as_f64().ok_or(constant)
+    }
+}
+
+impl TryFrom<Constant> for String {
+    type Error = Constant;
+
+    fn try_from(constant: Constant) -> Result<Self, Self::Error> {
+        match constant {
+            Constant::Symbol(value) => Ok(value),
+            constant => Err(constant),
+        }
+    }
+}
+
+impl TryFrom<Constant> for bool {
+    type Error = Constant;
+
+    fn try_from(constant: Constant) -> Result<Self, Self::Error> {
+        constant.as_bool().ok_or(constant)
+    }
+}
+
+/// [`Tuple`] is a row of a relation, typed by its column types.
+pub type Tuple = Vec<Constant>;
+
+/// a constant is written as in the source, see [`Constant::to_sql`] for its SQL literal
 impl Display for Constant {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Constant::Integer(value) => write!(f, "{}", value),
-            Constant::Float(value) => write!(f, "{}", value),
-            Constant::Symbol(value) => write!(f, "'{}'", value),
+            // keep the fraction or the exponent, e.g. `1.0` or `1.5e300`, so the literal is parsed back
+            // as the same float, `1.0` is written as `1` otherwise and becomes an integer in SQL
+            Constant::Float(value) => write!(f, "{:?}", value.into_inner()),
+            // a quote or a backslash inside a quoted symbol is escaped by a backslash
+            Constant::Symbol(value) => write!(f, "'{}'", value.replace('\\', "\\\\").replace('\'', "\\'")),
             Constant::Boolean(value) => write!(f, "{}", value),
         }
     }
@@ -242,10 +431,71 @@ pub enum Operator {
     Add,
     Sub,
     Mul,
+    /// truncating division between integers, and real division if any operand is a float,
+    /// a division by zero derives no tuple
     Div,
+    /// the remainder of a truncating division between integers, with the sign of the lhs,
+    /// a modulo by zero derives no tuple
+    Mod,
+    /// the aggregated operand, if any, is the rhs
+    Aggregate(Aggregate),
+    /// a built-in function of numbers, the operand of abs is the rhs,
+    /// and the operands of min and max are the lhs and the rhs
+    Function(Function),
+    /// `X in (a, b, c)` holds if the lhs equals one of the constants, the rhs is none
+    In(Vec<Constant>),
     Leaf(Term),
 }
 
+/// [`Aggregate`] folds the body tuples sharing the other head terms,
+/// degree(X, N) :- edge(X, _), N = count() counts the edges of each X,
+/// total(G, S) :- item(G, V), S = sum(V) sums V for each G,
+/// kinds(G, N) :- item(G, V), N = count_distinct(V) counts the distinct values of V for each G,
+/// popular(G) :- item(G, _), count() > 5 keeps the groups of more than five items,
+/// and the mean of avg is always a float, even over integers.
+#[derive(Debug, Clone)]
+pub enum Aggregate {
+    Count,
+    CountDistinct,
+    Sum,
+    Min,
+    Max,
+    Avg,
+}
+
+impl Display for Aggregate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Aggregate::Count => write!(f, "count"),
+            Aggregate::CountDistinct => write!(f, "count_distinct"),
+            Aggregate::Sum => write!(f, "sum"),
+            Aggregate::Min => write!(f, "min"),
+            Aggregate::Max => write!(f, "max"),
+            Aggregate::Avg => write!(f, "avg"),
+        }
+    }
+}
+
+/// [`Function`] is a built-in function of numbers within an expression,
+/// D = abs(X - Y) is the distance between X and Y, and L = min(X, Y) is the lesser of them.
+/// min and max of two operands are not the aggregates of one variable, min(X) folds the body tuples.
+#[derive(Debug, Clone)]
+pub enum Function {
+    Abs,
+    Min,
+    Max,
+}
+
+impl Display for Function {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Function::Abs => write!(f, "abs"),
+            Function::Min => write!(f, "min"),
+            Function::Max => write!(f, "max"),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Arith {
     pub operator: Operator,
@@ -253,7 +503,99 @@ pub struct Arith {
     pub rhs: Option<Box<Arith>>,
 }
 
+/// an expression is written as in the source, every nested operation is parenthesized
+impl Display for Arith {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let operand = |operand: &Option<Box<Arith>>| match operand.as_deref() {
+            Some(arith @ Arith { operator: Operator::Leaf(_) | Operator::Aggregate(_) | Operator::Function(_), .. }) => {
+                arith.to_string()
+            }
+            Some(arith) => format!("({})", arith),
+            None => String::new(),
+        };
+        let symbol = match &self.operator {
+            Operator::Leaf(term) => return write!(f, "{}", term),
+            Operator::Aggregate(aggregate) => {
+                let operand = self.rhs.as_ref().map(ToString::to_string).unwrap_or_default();
+                return write!(f, "{}({})", aggregate, operand);
+            }
+            Operator::Function(function) => {
+                let operands = [&self.lhs, &self.rhs].into_iter()
+                    .flatten()
+                    .map(ToString::to_string)
+                    .collect::<Vec<String>>();
+                return write!(f, "{}({})", function, operands.join(", "));
+            }
+            Operator::Neg => return write!(f, "!{}", operand(&self.rhs)),
+            Operator::Sub if self.lhs.is_none() => return write!(f, "-{}", operand(&self.rhs)),
+            Operator::In(constants) => {
+                let constants = constants.iter().map(ToString::to_string).collect::<Vec<String>>();
+                return write!(f, "{} in ({})", operand(&self.lhs), constants.join(", "));
+            }
+            Operator::Unifier => "==",
+            Operator::Disunifier => "!=",
+            Operator::Less => "<",
+            Operator::LessEqual => "<=",
+            Operator::Greater => ">",
+            Operator::GreaterEqual => ">=",
+            Operator::And => "&&",
+            Operator::Or => "||",
+            Operator::Add => "+",
+            Operator::Sub => "-",
+            Operator::Mul => "*",
+            Operator::Div => "/",
+            Operator::Mod => "%",
+        };
+        write!(f, "{} {} {}", operand(&self.lhs), symbol, operand(&self.rhs))
+    }
+}
+
 impl Arith {
+    /// `C = B + 10` assigns the expression `B + 10` to a fresh variable `C`,
+    /// if `C` is not bound and every variable in the expression is bound.
+    pub fn as_assignment(&self, is_bound: impl Fn(&String) -> bool) -> Option<(String, &Arith)> {
+        if !matches!(self.operator, Operator::Unifier) {
+            return None;
+        }
+        let lhs = self.lhs.as_ref()?;
+        let rhs = self.rhs.as_ref()?;
+        let all_bound = |arith: &Arith| {
+            arith.get_leaves().iter().all(|leaf| {
+                leaf.is_nontrivial_variable().is_none_or(|var| is_bound(&var))
+            })
+        };
+        for (var, expr) in [(lhs, rhs), (rhs, lhs)] {
+            if let Operator::Leaf(term) = &var.operator {
+                if let Some(var) = term.is_nontrivial_variable() {
+                    if !is_bound(&var) && all_bound(expr) {
+                        return Some((var, expr));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    pub fn is_aggregate(&self) -> bool {
+        matches!(self.operator, Operator::Aggregate(_))
+            || self.lhs.as_ref().is_some_and(|lhs| lhs.is_aggregate())
+            || self.rhs.as_ref().is_some_and(|rhs| rhs.is_aggregate())
+    }
+
+    /// a comparison, a membership or a logical operation, whose value is a boolean
+    pub fn is_condition(&self) -> bool {
+        matches!(self.operator,
+            Operator::Unifier | Operator::Disunifier
+            | Operator::Less | Operator::LessEqual | Operator::Greater | Operator::GreaterEqual
+            | Operator::And | Operator::Or | Operator::Neg | Operator::In(_))
+    }
+
+    pub fn has_division(&self) -> bool {
+        matches!(self.operator, Operator::Div | Operator::Mod)
+            || self.lhs.as_ref().is_some_and(|lhs| lhs.has_division())
+            || self.rhs.as_ref().is_some_and(|rhs| rhs.has_division())
+    }
+
     pub fn get_leaves(&self) -> Vec<Term> {
         let mut leaves = Vec::new();
         match &self.operator {
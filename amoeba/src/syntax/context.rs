@@ -1,4 +1,5 @@
 use super::ast::*;
+use super::error::{ContextError, NameResolutionError, StratificationError, UndefinedPredicate};
 use super::stratify::Stratum;
 use std::collections::{HashSet, HashMap};
 
@@ -7,31 +8,87 @@ pub struct Context {
     pub stratum: Stratum,
     pub edbs: HashMap<String, Rule>,
     pub idbs: HashMap<String, Vec<Rule>>,
+    /// @output rules, a rule with a body is a view derived only when queried
     pub queries: HashMap<String, Vec<Rule>>,
+    /// facts written in the source, inserted into the table of their edb
+    pub facts: HashMap<String, Vec<Atom>>,
 }
 
 impl Context {
-    pub fn new(program: &Program) -> Self {
+    pub fn new(program: &Program) -> Result<Self, ContextError> {
+        let program = &split_disjunctions(program);
+        check_arity(program)?;
         let mut edbs = HashMap::new();
         let mut idbs = HashMap::new();
         let mut queries = HashMap::new();
-        program.iter().for_each(|rule| {
+        let mut facts: HashMap<String, Vec<Atom>> = HashMap::new();
+        for rule in program.iter() {
             let name = rule.head.predicate.clone();
             match rule.io {
                 IO::Read(_) => {
-                    edbs.insert(name, rule.clone());
+                    // an edb may be declared only once, including across included files
+                    if edbs.insert(name.clone(), rule.clone()).is_some() {
+                        panic!("Duplicated predicate: {}", name);
+                    }
                 }
-                IO::Write(_) => {
+                IO::Write(_) | IO::WriteBag(_) => {
                     let rules = queries.entry(name)
                         .or_insert(Vec::new());
                     rules.push(rule.clone());
                 }
+                // a rule without a body is an inline fact, which must be ground,
+                // a variable in it would range over every value and the relation would be infinite
+                IO::Silent if rule.is_fact() => {
+                    if let Some(var) = rule.head.terms.iter().find(|term| matches!(term, Term::Variable(_))) {
+                        return Err(ContextError::EmptyBody {
+                            rule: rule.head.to_string(),
+                            var: var.to_string(),
+                        });
+                    }
+                    facts.entry(name).or_default().push(rule.head.clone());
+                }
                 IO::Silent => {
                     let rules = idbs.entry(name)
                         .or_insert(Vec::new());
                     rules.push(rule.clone());
                 }
             };
+        }
+        // an @input predicate is read from its table or csv file, so no rule may derive it,
+        // which also catches @input written above a rule rather than above a declaration
+        let derived_edb = program.iter().find(|rule| {
+            !rule.body.is_empty()
+                && matches!(rule.io, IO::Read(_) | IO::Silent)
+                && edbs.contains_key(&rule.head.predicate)
+        });
+        if let Some(rule) = derived_edb {
+            let body = rule.body.iter().map(Clause::to_string).collect::<Vec<String>>();
+            return Err(ContextError::PredicateIsBothEdbAndIdb {
+                predicate: rule.head.predicate.clone(),
+                rule: format!("{} :- {}", rule.head, body.join(", ")),
+            });
+        }
+        // facts of an undeclared predicate declare an edb typed by the first fact,
+        // a column mixing integers and floats is a float column
+        facts.iter().for_each(|(name, atoms)| {
+            if edbs.contains_key(name) {
+                return;
+            }
+            let terms = atoms[0].terms.iter().enumerate().map(|(i, term)| {
+                let type_ = match term {
+                    Term::Constant(Constant::Integer(_)) if atoms.iter().any(|atom| {
+                        matches!(atom.terms[i], Term::Constant(Constant::Float(_)))
+                    }) => "float",
+                    Term::Constant(Constant::Integer(_)) => "int",
+                    Term::Constant(Constant::Float(_)) => "float",
+                    Term::Constant(Constant::Symbol(_)) => "sym",
+                    Term::Constant(Constant::Boolean(_)) => "bool",
+                    Term::Variable(_) => unreachable!(),
+                };
+                Term::Constant(Constant::Symbol(type_.to_string()))
+            }).collect();
+            let head = Atom { predicate: name.clone(), terms, negation: false };
+            edbs.insert(name.clone(), Rule { io: IO::Read(None), head, body: Vec::new(), columns: Vec::new(), disjuncts: Vec::new() });
         });
         let mut predicates = HashSet::new();
         // name resolution for edbs
@@ -41,94 +98,225 @@ impl Context {
             }
             predicates.insert(name.clone());
         });
-        // check the validation of atom in head of an idb
-        let check_head = |atom: &Atom| {
-            atom.terms.iter().for_each(|term| {
-                if let Term::Variable(Variable::Free) = term {
-                    panic!("Free variable in head of idb: {}", atom.predicate)
-                }
-            });
-        };
-        // check the validation of atom in clauses of an idb
-        let check_atom= |atom: &Atom| {
-            let name = atom.predicate.clone();
-            if !predicates.contains(&name) {
-                // find a predicate in idb
-                if !idbs.contains_key(&name) {
-                    panic!("Undefined predicate: {}", name);
-                }
-            }
+        // a wildcard in the head of an idb or a view matches no value of its body
+        let check_head = |atom: &Atom| match atom.terms.iter().any(|term| matches!(term, Term::Variable(Variable::Free))) {
+            true => Err(ContextError::FreeVariableInHead { predicate: atom.predicate.clone() }),
+            false => Ok(()),
         };
+        resolve_names(program, &predicates, &idbs)?;
         let mut dependencies = HashSet::new();
-        idbs.iter().for_each(|(name, rules)| {
-            if predicates.contains(name) {
-                panic!("predicate declared as both idb and edb: {}", name);
-            }
+        for (name, rules) in idbs.iter() {
             for rule in rules {
-                check_head(&rule.head);
+                check_head(&rule.head)?;
                 rule.body.iter().for_each(|clause| {
                     if let Clause::Atom(atom) = clause {
-                        check_atom(atom);
-                        dependencies.insert((name, &atom.predicate));
+                        dependencies.insert((name, &atom.predicate, atom.negation));
                     }
                 });
             }
-        });
+        }
+        // a view reads every predicate, and is derived after all of them
+        for (name, rules) in queries.iter() {
+            let views = rules.iter().filter(|rule| !rule.body.is_empty()).collect::<Vec<&Rule>>();
+            if !views.is_empty() && (predicates.contains(name) || idbs.contains_key(name)) {
+                return Err(ContextError::ViewShadowsPredicate(name.clone()));
+            }
+            for rule in views {
+                check_head(&rule.head)?;
+            }
+        }
         // check stratum
         let stratum = Stratum::new(predicates, dependencies);
-        let check_stratum = |head_level: usize, clauses: &Vec<Clause>| {
-            for clause in clauses.iter() {
-                if let Clause::Atom(atom) = clause {
-                    if !atom.negation {
-                        continue;
-                    }
-                    let level = stratum.get_level(&atom.predicate);
-                    match head_level.cmp(&level) {
-                        std::cmp::Ordering::Less => panic!("Cyclic dependency: {:?}", atom),
-                        std::cmp::Ordering::Equal => panic!("Mutual dependency: {:?}", atom),
-                        std::cmp::Ordering::Greater => {}
-                    }
-                }
-            }
-        };
-        idbs.iter().for_each(|(name, rules)| {
-            let level = stratum.get_level(name);
+        // negated subgoals, and every subgoal of an aggregating rule,
+        // must be fully computed in a lower stratum
+        for (name, rules) in idbs.iter() {
             for rule in rules {
-                check_stratum(level, &rule.body);
+                check_stratum(&stratum, name, rule)?;
             }
-        });
+        }
         // check variable safety
-        // a rule is safe if:
-        // 1. each distinguished variable
-        // 2. each variable in arithmetic subgoal
-        // 3. each variable in a negated subgoal
-        // also appears in a non-negated, relational subgoal
-        idbs.iter_mut().for_each(|(_, rules)| {
-            rules.iter_mut().for_each(|rule| {
+        for rules in idbs.values().chain(queries.values()) {
+            for rule in rules.iter().filter(|rule| !rule.body.is_empty()) {
+                check_safety(rule)?;
+            }
+        }
+        idbs.iter_mut().chain(queries.iter_mut()).for_each(|(_, rules)| {
+            rules.iter_mut().filter(|rule| !rule.body.is_empty()).for_each(|rule| {
                 rule.annotate_variable();
             })
         });
-        // print stratum
-        // stratum.strata.iter().enumerate().for_each(|(level, predicates)| {
-        //     println!("Stratum {}", level);
-        //     predicates.iter().for_each(|predicate| {
-        //         println!("  {}", predicate);
-        //     });
-        // });
-        Self { stratum, edbs, idbs, queries }
+        Ok(Self { stratum, edbs, idbs, queries, facts })
     }
 
     pub fn ordered_idbs(&self) -> Vec<String> {
         // give queue of idbs' name according to stratum's order
-        // filter stratum's name that is an edb
+        // filter stratum's name that is an edb, or an idb left out by `select_query`
         let mut queue = Vec::new();
         self.stratum.strata.iter().for_each(|predicates| {
             predicates.iter().for_each(|predicate| {
-                if !self.edbs.contains_key(predicate) {
+                if self.idbs.contains_key(predicate) {
                     queue.push(predicate.clone());
                 }
             });
         });
         queue
     }
-}
\ No newline at end of file
+
+    /// keep only the query on `name` and the idbs it depends on,
+    /// so that every unrelated idb is left unevaluated
+    pub fn select_query(&mut self, name: &str) {
+        self.queries.retain(|query, _| query == name);
+        let mut relevant = HashSet::new();
+        for rule in self.queries.values().flatten() {
+            if rule.body.is_empty() {
+                relevant.extend(self.stratum.dependencies_of(&rule.head.predicate));
+            }
+            // a view depends on the predicates of its body
+            rule.body.iter().for_each(|clause| {
+                if let Clause::Atom(atom) = clause {
+                    relevant.extend(self.stratum.dependencies_of(&atom.predicate));
+                }
+            });
+        }
+        self.idbs.retain(|idb, _| relevant.contains(idb));
+    }
+}
+
+/// a subgoal which must be computed before the head, but is in the same stratum,
+/// depends back on the head, and the cycle through it is reported.
+/// a predicate missing from the dependency graph is reported as undefined
+fn check_stratum(stratum: &Stratum, name: &String, rule: &Rule) -> Result<(), ContextError> {
+    let aggregate = rule.has_aggregate();
+    for (index, clause) in rule.body.iter().enumerate() {
+        if let Clause::Atom(atom) = clause {
+            if !atom.negation && !aggregate {
+                continue;
+            }
+            let level = |predicate: &String| stratum.get_level(predicate).ok_or_else(|| {
+                ContextError::NameResolution(NameResolutionError {
+                    undefined: vec![UndefinedPredicate {
+                        predicate: predicate.clone(),
+                        rule: rule.head.to_string(),
+                        subgoal: index + 1,
+                    }],
+                })
+            });
+            if level(name)? > level(&atom.predicate)? {
+                continue;
+            }
+            let path = stratum.path(&atom.predicate, name)
+                .expect("Predicates in the same stratum should depend on each other");
+            let mut cycle = vec![(name.clone(), false), (atom.predicate.clone(), atom.negation)];
+            cycle.extend(path);
+            return Err(ContextError::Stratification(StratificationError {
+                cycle,
+                aggregate: !atom.negation,
+            }));
+        }
+    }
+    Ok(())
+}
+
+/// every subgoal of a rule is on an edb or an idb,
+/// the subgoals on undefined predicates are all reported at once
+fn resolve_names(
+    program: &Program,
+    edbs: &HashSet<String>,
+    idbs: &HashMap<String, Vec<Rule>>
+) -> Result<(), ContextError> {
+    let mut undefined = Vec::new();
+    for rule in program {
+        for (index, clause) in rule.body.iter().enumerate() {
+            if let Clause::Atom(atom) = clause {
+                if !edbs.contains(&atom.predicate) && !idbs.contains_key(&atom.predicate) {
+                    undefined.push(UndefinedPredicate {
+                        predicate: atom.predicate.clone(),
+                        rule: rule.head.to_string(),
+                        subgoal: index + 1,
+                    });
+                }
+            }
+        }
+    }
+    if undefined.is_empty() {
+        return Ok(());
+    }
+    Err(ContextError::NameResolution(NameResolutionError { undefined }))
+}
+
+/// a rule with a disjunctive body is split into one rule per disjunct, all of them with the same head
+fn split_disjunctions(program: &Program) -> Program {
+    program.iter().flat_map(|rule| {
+        std::iter::once(rule.body.clone())
+            .chain(rule.disjuncts.iter().cloned())
+            .map(|body| Rule { body, disjuncts: Vec::new(), ..rule.clone() })
+    }).collect()
+}
+
+/// every occurrence of a predicate, in a head or in a body,
+/// must have as many terms as the first occurrence
+fn check_arity(program: &Program) -> Result<(), ContextError> {
+    let mut arities: HashMap<&String, usize> = HashMap::new();
+    for rule in program {
+        let atoms = std::iter::once(&rule.head).chain(rule.body.iter().filter_map(|clause| match clause {
+            Clause::Atom(atom) => Some(atom),
+            Clause::Arithmetic(_) => None,
+        }));
+        for atom in atoms {
+            let expected = *arities.entry(&atom.predicate).or_insert(atom.terms.len());
+            if atom.terms.len() != expected {
+                return Err(ContextError::ArityMismatch {
+                    predicate: atom.predicate.clone(),
+                    expected,
+                    actual: atom.terms.len(),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// a rule is safe if each distinguished variable, each variable in an arithmetic subgoal
+/// and each variable in a negated subgoal also appears in a non-negated, relational subgoal,
+/// or is assigned by arithmetic over such variables, e.g. `C = B + 10`
+fn check_safety(rule: &Rule) -> Result<(), ContextError> {
+    let mut bound = HashSet::new();
+    rule.body.iter().for_each(|clause| {
+        if let Clause::Atom(atom) = clause {
+            if !atom.negation {
+                bound.extend(atom.terms.iter().filter_map(Term::is_nontrivial_variable));
+            }
+        }
+    });
+    // an assignment may use variables assigned by earlier clauses
+    rule.body.iter().for_each(|clause| {
+        if let Clause::Arithmetic(arith) = clause {
+            if let Some((var, _)) = arith.as_assignment(|var| bound.contains(var)) {
+                bound.insert(var);
+            }
+        }
+    });
+    let unsafe_rule = |var: String, location: String| ContextError::UnsafeRule {
+        predicate: rule.head.predicate.clone(),
+        var,
+        location,
+    };
+    for term in rule.head.terms.iter() {
+        if let Some(var) = term.is_nontrivial_variable().filter(|var| !bound.contains(var)) {
+            return Err(unsafe_rule(var, "the head".to_string()));
+        }
+    }
+    for clause in rule.body.iter() {
+        let (terms, location) = match clause {
+            Clause::Atom(atom) if atom.negation => (atom.terms.clone(), format!("the negated subgoal {}", atom)),
+            Clause::Atom(_) => continue,
+            Clause::Arithmetic(arith) => (arith.get_leaves(), "an arithmetic subgoal".to_string()),
+        };
+        for term in terms {
+            if let Some(var) = term.is_nontrivial_variable().filter(|var| !bound.contains(var)) {
+                return Err(unsafe_rule(var, location));
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,206 @@
+use std::error::Error;
+use std::fmt::Display;
+
+/// [`ParseError`] points at the position where parsing stopped.
+/// line and column are 1-based, snippet is the whole offending line.
+#[derive(Debug, Clone)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub snippet: String,
+}
+
+impl ParseError {
+    /// locate `remain`, the unparsed suffix of `source`, in the source
+    pub fn new(source: &str, remain: &str) -> Self {
+        let offset = source.len() - remain.len();
+        let line_start = source[..offset].rfind('\n').map_or(0, |index| index + 1);
+        let line_end = source[offset..].find('\n').map_or(source.len(), |index| offset + index);
+        let line = source[..offset].matches('\n').count() + 1;
+        let column = source[line_start..offset].chars().count() + 1;
+        let snippet = source[line_start..line_end].trim_end_matches('\r').to_string();
+        Self { line, column, snippet }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Parsing error at line {}, column {}:", self.line, self.column)?;
+        writeln!(f, "{}", self.snippet)?;
+        write!(f, "{}^", " ".repeat(self.column - 1))
+    }
+}
+
+impl Error for ParseError {}
+
+/// [`StratificationError`] is a cycle of dependencies through a negated subgoal,
+/// or through a subgoal of an aggregating rule, so that no predicate of the cycle can be computed first.
+#[derive(Debug, Clone)]
+pub struct StratificationError {
+    /// the predicates of the cycle, starting and ending with the same predicate,
+    /// each paired with whether it is negated in the body of the previous one
+    pub cycle: Vec<(String, bool)>,
+    /// whether the cycle goes through the subgoal of an aggregating rule rather than a negation
+    pub aggregate: bool,
+}
+
+impl Display for StratificationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cycle = self.cycle.iter()
+            .map(|(predicate, negation)| if *negation { format!("not {}", predicate) } else { predicate.clone() })
+            .collect::<Vec<String>>();
+        if self.aggregate {
+            write!(f, "Unstratifiable aggregation: {}", cycle.join(" -> "))
+        } else {
+            write!(f, "Unstratifiable: {}", cycle.join(" -> "))
+        }
+    }
+}
+
+impl Error for StratificationError {}
+
+/// [`NameResolutionError`] lists every subgoal on a predicate which is neither an edb nor an idb,
+/// in the order they occur in the source.
+#[derive(Debug, Clone)]
+pub struct NameResolutionError {
+    pub undefined: Vec<UndefinedPredicate>,
+}
+
+/// a subgoal on an undefined predicate, at a 1-based position in the body of a rule
+#[derive(Debug, Clone)]
+pub struct UndefinedPredicate {
+    pub predicate: String,
+    /// the head of the rule
+    pub rule: String,
+    pub subgoal: usize,
+}
+
+impl Display for NameResolutionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Undefined predicates:")?;
+        for undefined in self.undefined.iter() {
+            write!(f, "\n  {} in subgoal {} of {}", undefined.predicate, undefined.subgoal, undefined.rule)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for NameResolutionError {}
+
+/// [`ContextError`] is raised while checking a parsed program.
+#[derive(Debug, Clone)]
+pub enum ContextError {
+    /// a predicate is used with a different number of terms than its first occurrence
+    ArityMismatch {
+        predicate: String,
+        expected: usize,
+        actual: usize,
+    },
+    /// a variable of a rule does not appear in any positive relational subgoal,
+    /// nor is it assigned by arithmetic
+    UnsafeRule {
+        predicate: String,
+        var: String,
+        location: String,
+    },
+    /// an @output rule with a body defines a view named after an edb or idb
+    ViewShadowsPredicate(String),
+    /// a rule without a body is not a ground fact, a variable of its head is bound by nothing
+    EmptyBody {
+        rule: String,
+        var: String,
+    },
+    /// the head of a rule with a body has a wildcard `_`, which no value of the body binds
+    FreeVariableInHead {
+        predicate: String,
+    },
+    /// a predicate declared by @input is also derived by a rule
+    PredicateIsBothEdbAndIdb {
+        predicate: String,
+        rule: String,
+    },
+    NameResolution(NameResolutionError),
+    Stratification(StratificationError),
+}
+
+impl Display for ContextError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContextError::ArityMismatch { predicate, expected, actual } => {
+                write!(f, "Predicate {} is used with {} terms, but {} are expected", predicate, actual, expected)
+            }
+            ContextError::UnsafeRule { predicate, var, location } => {
+                write!(f, "Rule of {} is unsafe, variable {} in {} does not appear in a positive subgoal", predicate, var, location)
+            }
+            ContextError::ViewShadowsPredicate(predicate) => {
+                write!(f, "Query {} has a body, but {} is already defined by other rules", predicate, predicate)
+            }
+            ContextError::EmptyBody { rule, var } => {
+                write!(f, "Rule {} has no body, but variable {} in its head is not bound, only a ground fact may have no body", rule, var)
+            }
+            ContextError::FreeVariableInHead { predicate } => {
+                write!(f, "Rule of {} has `_` in its head, every term of a head must be a constant or a variable of the body", predicate)
+            }
+            ContextError::PredicateIsBothEdbAndIdb { predicate, rule } => {
+                write!(f, "Predicate {} is declared by @input, but is also derived by the rule {}, \
+                    an @input predicate is only read, remove the annotation or rename one of them", predicate, rule)
+            }
+            ContextError::NameResolution(error) => write!(f, "{}", error),
+            ContextError::Stratification(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl Error for ContextError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ContextError::NameResolution(error) => Some(error),
+            ContextError::Stratification(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// [`SourceError`] is raised while reading a source and the files it includes.
+#[derive(Debug)]
+pub enum SourceError {
+    Io {
+        file: String,
+        error: std::io::Error,
+    },
+    Parse {
+        file: String,
+        error: ParseError,
+    },
+    /// a file includes itself, directly or through other files
+    IncludeCycle(String),
+    Context(ContextError),
+}
+
+impl Display for SourceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SourceError::Io { file, error } => write!(f, "{}: {}", file, error),
+            SourceError::Parse { file, error } => write!(f, "{}: {}", file, error),
+            SourceError::IncludeCycle(file) => write!(f, "{} includes itself", file),
+            SourceError::Context(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl Error for SourceError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SourceError::Io { error, .. } => Some(error),
+            SourceError::Parse { error, .. } => Some(error),
+            SourceError::IncludeCycle(_) => None,
+            SourceError::Context(error) => Some(error),
+        }
+    }
+}
+
+impl From<ContextError> for SourceError {
+    fn from(error: ContextError) -> Self {
+        SourceError::Context(error)
+    }
+}
@@ -0,0 +1,221 @@
+use super::ast::*;
+use super::context::Context;
+use super::error::ContextError;
+use std::collections::HashSet;
+
+/// rewrite a program with magic sets, so that a query with constant arguments,
+/// e.g. reachable(a, Y), only derives the tuples relevant to its constants.
+///
+/// each argument of a predicate is adorned as bound (`b`) or free (`f`).
+/// `adorned_reachable_bf` is `reachable` restricted to the first arguments in `magic_reachable_bf`,
+/// which is seeded by the constants of the queries in `seed_reachable_bf`,
+/// and bindings are passed from left to right through the body of a rule.
+/// a predicate used without any bound argument, negated or aggregated is derived in full,
+/// and only the predicates needed by the queries are kept.
+///
+/// the evaluator derives the predicates of a stratum one at a time, so a rewrite which makes
+/// predicates mutually recursive, e.g. through a nonlinear rule, leaves the program unrewritten.
+pub fn rewrite(context: &Context) -> Result<Context, ContextError> {
+    let mut program = Vec::new();
+    context.edbs.values().for_each(|rule| program.push(rule.clone()));
+    context.facts.values().flatten().for_each(|atom| program.push(fact(atom.clone())));
+    let mut full = Vec::new();
+    let mut queue = Vec::new();
+    let mut bridges = Vec::new();
+    for (name, rules) in context.queries.iter() {
+        for rule in rules {
+            program.push(rule.clone());
+            // a view reads the predicates of its body in full
+            if !rule.body.is_empty() {
+                rule.body.iter().for_each(|clause| {
+                    if let Clause::Atom(atom) = clause {
+                        full.push(atom.predicate.clone());
+                    }
+                });
+                continue;
+            }
+            let adornment = rule.head.terms.iter()
+                .map(|term| if let Term::Constant(_) = term { 'b' } else { 'f' })
+                .collect::<String>();
+            if !is_adornable(context, name) || !adornment.contains('b') {
+                full.push(name.clone());
+                continue;
+            }
+            let constants = rule.head.terms.iter()
+                .filter(|term| matches!(term, Term::Constant(_)))
+                .cloned()
+                .collect();
+            program.push(fact(atom(&seed_name(name, &adornment), constants)));
+            if queue.contains(&(name.clone(), adornment.clone())) {
+                continue;
+            }
+            // magic_p_bf(V0) :- seed_p_bf(V0), and p(V0, V1) :- adorned_p_bf(V0, V1)
+            let bound = (0..adornment.matches('b').count()).map(variable).collect::<Vec<Term>>();
+            let seed = atom(&seed_name(name, &adornment), bound.clone());
+            program.push(rule_of(atom(&magic_name(name, &adornment), bound), vec![seed]));
+            let terms = (0..adornment.len()).map(variable).collect::<Vec<Term>>();
+            let adorned = atom(&adorned_name(name, &adornment), terms.clone());
+            bridges.push(rule_of(atom(name, terms), vec![adorned]));
+            queue.push((name.clone(), adornment));
+        }
+    }
+    let mut adorned = HashSet::new();
+    while let Some((name, adornment)) = queue.pop() {
+        if !adorned.insert((name.clone(), adornment.clone())) {
+            continue;
+        }
+        let rules = context.idbs.get(&name)
+            .expect("IDB should be present in context");
+        for rule in rules {
+            program.append(&mut adorn_rule(context, rule, &adornment, &mut queue, &mut full));
+        }
+    }
+    // predicates derived in full only depend on predicates derived in full
+    let mut derived = HashSet::new();
+    while let Some(name) = full.pop() {
+        if !derived.insert(name.clone()) {
+            continue;
+        }
+        if let Some(rules) = context.idbs.get(&name) {
+            for rule in rules {
+                program.push(rule.clone());
+                rule.body.iter().for_each(|clause| {
+                    if let Clause::Atom(atom) = clause {
+                        full.push(atom.predicate.clone());
+                    }
+                });
+            }
+        }
+    }
+    // a predicate derived in full already holds the tuples of its adorned predicates
+    bridges.into_iter()
+        .filter(|bridge| !derived.contains(&bridge.head.predicate))
+        .for_each(|bridge| program.push(bridge));
+    let rewritten = Context::new(&program)?;
+    if rewritten.stratum.strata.iter().any(|stratum| stratum.len() > 1) {
+        return Ok(context.clone());
+    }
+    Ok(rewritten)
+}
+
+/// the adorned rule of `p_adornment`, preceded by the magic rules
+/// passing the bindings to each adorned predicate in its body
+fn adorn_rule(
+    context: &Context,
+    rule: &Rule,
+    adornment: &str,
+    queue: &mut Vec<(String, String)>,
+    full: &mut Vec<String>
+) -> Vec<Rule> {
+    let name = &rule.head.predicate;
+    let magic = atom(&magic_name(name, adornment), bound_terms(&rule.head, adornment));
+    let mut bound = variables(&magic);
+    let mut body = vec![Clause::Atom(magic)];
+    let mut rules = Vec::new();
+    for clause in rule.body.iter() {
+        match clause {
+            Clause::Atom(subgoal) if !subgoal.negation && is_adornable(context, &subgoal.predicate) => {
+                let adornment = subgoal.terms.iter()
+                    .map(|term| match term.is_nontrivial_variable() {
+                        Some(var) if bound.contains(&var) => 'b',
+                        _ => 'f',
+                    })
+                    .collect::<String>();
+                if !adornment.contains('b') {
+                    full.push(subgoal.predicate.clone());
+                    bound.extend(variables(subgoal));
+                    body.push(clause.clone());
+                    continue;
+                }
+                let head = atom(&magic_name(&subgoal.predicate, &adornment), bound_terms(subgoal, &adornment));
+                rules.push(Rule { io: IO::Silent, head, body: safe_prefix(&body, &bound), columns: Vec::new(), disjuncts: Vec::new() });
+                let predicate = adorned_name(&subgoal.predicate, &adornment);
+                queue.push((subgoal.predicate.clone(), adornment));
+                bound.extend(variables(subgoal));
+                body.push(Clause::Atom(Atom { predicate, ..subgoal.clone() }));
+            }
+            Clause::Atom(subgoal) => {
+                if context.idbs.contains_key(&subgoal.predicate) {
+                    full.push(subgoal.predicate.clone());
+                }
+                if !subgoal.negation {
+                    bound.extend(variables(subgoal));
+                }
+                body.push(clause.clone());
+            }
+            Clause::Arithmetic(arith) => {
+                if let Some((var, _)) = arith.as_assignment(|var| bound.contains(var)) {
+                    bound.insert(var);
+                }
+                body.push(clause.clone());
+            }
+        }
+    }
+    let head = Atom { predicate: adorned_name(name, adornment), ..rule.head.clone() };
+    rules.push(Rule { io: IO::Silent, head, body, columns: Vec::new(), disjuncts: Vec::new() });
+    rules
+}
+
+/// the clauses of a body whose variables are all bound,
+/// a filter left out of a magic rule only lets more bindings through
+fn safe_prefix(body: &[Clause], bound: &HashSet<String>) -> Vec<Clause> {
+    body.iter().filter(|clause| {
+        let leaves = match clause {
+            Clause::Atom(atom) if !atom.negation => return true,
+            Clause::Atom(atom) => atom.terms.clone(),
+            Clause::Arithmetic(arith) => arith.get_leaves(),
+        };
+        leaves.iter().all(|leaf| leaf.is_nontrivial_variable().is_none_or(|var| bound.contains(&var)))
+    }).cloned().collect()
+}
+
+/// a predicate is adorned if it is an idb without aggregates
+fn is_adornable(context: &Context, name: &str) -> bool {
+    context.idbs.get(name).is_some_and(|rules| !rules.iter().any(Rule::has_aggregate))
+}
+
+fn bound_terms(atom: &Atom, adornment: &str) -> Vec<Term> {
+    atom.terms.iter()
+        .zip(adornment.chars())
+        .filter(|(_, adornment)| *adornment == 'b')
+        .map(|(term, _)| term.clone())
+        .collect()
+}
+
+fn variables(atom: &Atom) -> HashSet<String> {
+    atom.terms.iter().filter_map(Term::is_nontrivial_variable).collect()
+}
+
+fn variable(index: usize) -> Term {
+    Term::Variable(Variable::Undistinguished(format!("V{}", index)))
+}
+
+fn atom(predicate: &str, terms: Vec<Term>) -> Atom {
+    Atom { negation: false, predicate: predicate.to_string(), terms }
+}
+
+fn fact(head: Atom) -> Rule {
+    Rule { io: IO::Silent, head, body: Vec::new(), columns: Vec::new(), disjuncts: Vec::new() }
+}
+
+fn rule_of(head: Atom, body: Vec<Atom>) -> Rule {
+    Rule {
+        io: IO::Silent,
+        head,
+        body: body.into_iter().map(Clause::Atom).collect(),
+        columns: Vec::new(),
+        disjuncts: Vec::new(),
+    }
+}
+
+fn adorned_name(name: &str, adornment: &str) -> String {
+    format!("adorned_{}_{}", name, adornment)
+}
+
+fn magic_name(name: &str, adornment: &str) -> String {
+    format!("magic_{}_{}", name, adornment)
+}
+
+fn seed_name(name: &str, adornment: &str) -> String {
+    format!("seed_{}_{}", name, adornment)
+}
@@ -2,16 +2,91 @@ mod parser;
 mod stratify;
 pub mod ast;
 pub mod context;
-use std::fs::read_to_string;
-use parser::parse_program;
+pub mod error;
+pub mod magic;
+use parser::{parse_program, parse_rules, Item};
+use ast::{Atom, Program};
 use context::Context;
+use error::{ParseError, SourceError};
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+/// parse a program, included files are looked up relative to the working directory
+///
+/// ```
+/// use amoeba::syntax::{parse, error::SourceError};
+///
+/// // the missing term is reported where it is expected, with a caret under its column
+/// let Err(SourceError::Parse { error, .. }) = parse("edge(1, ).") else {
+///     panic!("a missing term should not parse");
+/// };
+/// assert_eq!((error.line, error.column), (1, 9));
+/// assert!(error.to_string().ends_with("edge(1, ).\n        ^"));
+/// ```
+pub fn parse(input: &str) -> Result<Context, SourceError> {
+    let program = expand(input, "<input>", Path::new(""), &mut Vec::new())?;
+    Ok(Context::new(&program)?)
+}
+
+/// parse a source file, included files are looked up relative to the including file
+pub fn parse_file(path: &Path) -> Result<Context, SourceError> {
+    let program = load(path, &mut Vec::new())?;
+    Ok(Context::new(&program)?)
+}
+
+/// parse a single atom, e.g. `reachable(a, Y)`
+pub fn parse_atom(input: &str) -> Result<Atom, ParseError> {
+    let input = input.trim();
+    match parser::parse_atom(input) {
+        Ok(("", atom)) => Ok(atom),
+        Ok((remain, _)) => Err(ParseError::new(input, remain)),
+        Err(nom::Err::Error(error) | nom::Err::Failure(error)) => Err(ParseError::new(input, error.input)),
+        Err(nom::Err::Incomplete(_)) => Err(ParseError::new(input, "")),
+    }
+}
 
-pub fn parse(source: &str) -> Context {
-    let input = read_to_string(source).unwrap();
-    let (remain, program) = parse_program(&input).unwrap();
-    if !remain.is_empty() {
-        panic!("Parsing error:\nparsing remain: \"{}\"", remain);
+/// read a file and splice in the files it includes,
+/// `stack` holds the files being included to detect a cycle
+fn load(path: &Path, stack: &mut Vec<PathBuf>) -> Result<Program, SourceError> {
+    let file = path.display().to_string();
+    let io_error = |error| SourceError::Io { file: file.clone(), error };
+    let canonical = path.canonicalize().map_err(io_error)?;
+    if stack.contains(&canonical) {
+        return Err(SourceError::IncludeCycle(file));
     }
-    let context = Context::new(&program);
-    context
+    let input = read_to_string(path).map_err(io_error)?;
+    stack.push(canonical);
+    let directory = path.parent().unwrap_or(Path::new(""));
+    let program = expand(&input, &file, directory, stack)?;
+    stack.pop();
+    Ok(program)
+}
+
+fn expand(input: &str, file: &str, directory: &Path, stack: &mut Vec<PathBuf>) -> Result<Program, SourceError> {
+    let items = parse_items(input).map_err(|error| SourceError::Parse {
+        file: file.to_string(),
+        error,
+    })?;
+    let mut program = Vec::new();
+    for item in items {
+        match item {
+            Item::Rule(rule) => program.push(rule),
+            Item::Include(include) => program.append(&mut load(&directory.join(include), stack)?),
+        }
+    }
+    Ok(program)
+}
+
+fn parse_items(input: &str) -> Result<Vec<Item>, ParseError> {
+    let remain = match parse_program(input) {
+        Ok(("", items)) => return Ok(items),
+        // parse the remaining rule again to find where it fails
+        Ok((remain, _)) => match parse_rules(remain) {
+            Err(nom::Err::Error(error) | nom::Err::Failure(error)) => error.input,
+            _ => remain,
+        },
+        Err(nom::Err::Error(error) | nom::Err::Failure(error)) => error.input,
+        Err(nom::Err::Incomplete(_)) => "",
+    };
+    Err(ParseError::new(input, remain))
 }
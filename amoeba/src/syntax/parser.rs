@@ -1,27 +1,43 @@
 use super::ast::*;
 use nom::IResult;
 use nom::branch::alt;
-use nom::bytes::complete::{tag, take_while1, take_until};
-use nom::sequence::{delimited, tuple, preceded};
-use nom::combinator::{opt, map, verify};
-use nom::multi::{separated_list1, many0};
-use nom::character::complete::multispace0;
+use nom::bytes::complete::{tag, take_while1, take_until, escaped_transform};
+use nom::sequence::{delimited, tuple, preceded, terminated, pair};
+use nom::combinator::{opt, map, map_res, verify, not, cut, recognize, value, consumed, peek};
+use nom::multi::{separated_list1, many0, fold_many0};
+use nom::character::complete::{multispace0, space0, satisfy, digit1, char, none_of, one_of, not_line_ending, line_ending};
 use std::str::FromStr;
 use ordered_float::NotNan;
 
 fn parse_symbol(input: &str) -> IResult<&str, String> {
     let (input, symbol) = verify(
         take_while1(|c: char| c.is_alphanumeric() ||  c == '_'),
-        |s: &str| s.chars().next().unwrap().is_ascii_lowercase() || s.chars().next().unwrap() == '_'
+        |s: &str| s.chars().next().unwrap().is_ascii_lowercase() || s.starts_with('_')
     )(input)?;
     let (input, _) = multispace0(input)?;
     Ok((input, symbol.to_string()))
 }
 
+/// a single or double quoted symbol, e.g. "New York" or 'O\'Brien',
+/// a quote of the same kind or a backslash inside is escaped by a backslash
+fn parse_string(input: &str) -> IResult<&str, String> {
+    let escape = || alt((
+        value("\\", tag("\\")),
+        value("\"", tag("\"")),
+        value("'", tag("'")),
+    ));
+    let (input, string) = alt((
+        delimited(char('"'), opt(escaped_transform(none_of("\\\""), '\\', escape())), char('"')),
+        delimited(char('\''), opt(escaped_transform(none_of("\\'"), '\\', escape())), char('\'')),
+    ))(input)?;
+    let (input, _) = multispace0(input)?;
+    Ok((input, string.unwrap_or_default()))
+}
+
 fn parse_variable(input: &str) -> IResult<&str, Variable> {
     let (input, variable) = verify(
         take_while1(|c: char| c.is_alphanumeric() ||  c == '_'),
-        |s: &str| s.chars().next().unwrap().is_ascii_uppercase() || s.chars().next().unwrap() == '_'
+        |s: &str| s.chars().next().unwrap().is_ascii_uppercase() || s.starts_with('_')
     )(input)?;
     if variable == "_" {
         return Ok((input, Variable::Free));
@@ -29,77 +45,121 @@ fn parse_variable(input: &str) -> IResult<&str, Variable> {
     Ok((input, Variable::Undistinguished(variable.to_string())))
 }
 
-fn parse_float(input: &str) -> IResult<&str, f64> {
-    let (input, (int_part, frac_part)) = tuple((
-        take_while1(|c: char| c.is_numeric()),
-        opt(tuple((tag("."), take_while1(|c: char| c.is_numeric()))))
-    ))(input)?;
-    let float = match frac_part {
-        Some((_, frac_part)) => format!("{}.{}", int_part, frac_part),
-        None => int_part.to_string(),
-    };
-    let float = f64::from_str(&float).unwrap();
-    Ok((input, float))
+// a leading `-` belongs to the literal, e.g. offset(-5),
+// in arithmetic the unary `-` is consumed by `parse_unary` before reaching a term.
+// an exponent is written as `1.5e10` or `1e-7`, as a float is displayed.
+// a literal too large for a float is rejected where it starts, rather than read as infinity
+fn parse_float(input: &str) -> IResult<&str, NotNan<f64>> {
+    let exponent = || recognize(tuple((one_of("eE"), opt(one_of("+-")), digit1)));
+    let (remain, literal) = recognize(tuple((
+        opt(tag("-")),
+        digit1,
+        alt((recognize(pair(pair(tag("."), digit1), opt(exponent()))), exponent()))
+    )))(input)?;
+    match f64::from_str(literal).ok().filter(|float| float.is_finite()) {
+        Some(float) => Ok((remain, NotNan::new(float).expect("Finite float should not be NaN"))),
+        None => Err(nom::Err::Failure(nom::error::Error::new(input, nom::error::ErrorKind::Float))),
+    }
 }
 
 fn parse_integer(input: &str) -> IResult<&str, i64> {
-    let (input, integer) = take_while1(|c: char| c.is_numeric())(input)?;
-    let integer = i64::from_str(integer).unwrap();
-    Ok((input, integer))
+    map_res(
+        recognize(pair(opt(tag("-")), digit1)),
+        i64::from_str
+    )(input)
 }
 
 fn parse_boolean(input: &str) -> IResult<&str, bool> {
-    let (input, boolean) = alt((
-        map(tag("true"), |_| true),
-        map(tag("false"), |_| false),
-    ))(input)?;
+    let (input, boolean) = terminated(
+        alt((
+            map(tag("true"), |_| true),
+            map(tag("false"), |_| false),
+        )),
+        // `trueish` is a symbol rather than a boolean
+        not(satisfy(|c: char| c.is_alphanumeric() || c == '_'))
+    )(input)?;
     Ok((input, boolean))
 }
 
 fn parse_term(input: &str) -> IResult<&str, Term> {
     let (input, term) = alt((
-        map(parse_variable, |variable| Term::Variable(variable)),
-        map(parse_float, |float| Term::Constant(Constant::Float(NotNan::new(float).unwrap()))),
+        map(parse_variable, Term::Variable),
+        map(parse_float, |float| Term::Constant(Constant::Float(float))),
         map(parse_integer, |integer| Term::Constant(Constant::Integer(integer))),
-        map(parse_symbol, |symbol| Term::Constant(Constant::Symbol(symbol))),
         map(parse_boolean, |boolean| Term::Constant(Constant::Boolean(boolean))),
+        map(parse_symbol, |symbol| Term::Constant(Constant::Symbol(symbol))),
+        map(parse_string, |string| Term::Constant(Constant::Symbol(string))),
     ))(input)?;
     let (input, _) = multispace0(input)?;
     Ok((input, term))
 }
 
 fn parse_term_list(input: &str) -> IResult<&str, Vec<Term>> {
-    let (input, terms) = delimited(
+    let (input, (first, mut rest)) = delimited(
         tuple((multispace0, tag("("), multispace0)),
-        separated_list1(tuple(
-            (multispace0, tag(","), multispace0)
-        ), parse_term),
+        // a term must follow a comma, so a missing term is reported where it is expected
+        pair(parse_term, many0(preceded(
+            tuple((multispace0, tag(","), multispace0)),
+            cut(parse_term)
+        ))),
         tuple((multispace0, tag(")"), multispace0))
     )(input)?;
+    let mut terms = vec![first];
+    terms.append(&mut rest);
     Ok((input, terms))
 }
 
+/// a column of an edb declaration, optionally named, e.g. `src: sym`
+fn parse_column(input: &str) -> IResult<&str, (Option<String>, Term)> {
+    pair(
+        opt(terminated(parse_symbol, tuple((multispace0, tag(":"), multispace0)))),
+        parse_term
+    )(input)
+}
+
+/// the head of an @input declaration, whose columns may be named, e.g. `edge(src: sym, dst: sym)`
+fn parse_declaration(input: &str) -> IResult<&str, (Atom, Vec<Option<String>>)> {
+    let (input, predicate) = parse_symbol(input)?;
+    let (input, (first, rest)) = delimited(
+        tuple((multispace0, tag("("), multispace0)),
+        pair(parse_column, many0(preceded(
+            tuple((multispace0, tag(","), multispace0)),
+            cut(parse_column)
+        ))),
+        tuple((multispace0, tag(")"), multispace0))
+    )(input)?;
+    let (mut columns, terms): (Vec<Option<String>>, Vec<Term>) = std::iter::once(first).chain(rest).unzip();
+    if columns.iter().all(Option::is_none) {
+        columns.clear();
+    }
+    let atom = Atom { predicate, terms, negation: false };
+    Ok((input, (atom, columns)))
+}
+
 fn parse_annotator(input: &str) -> IResult<&str, IO> {
-    let (input, io) = alt((
-        map(delimited(multispace0, tag("@input"), multispace0), |_| IO::Read(None)),
-        map(delimited(multispace0, tag("@output"), multispace0), |_| IO::Write(None)),
+    let (input, annotator) = delimited(
+        multispace0,
+        alt((tag("@input"), tag("@output_bag"), tag("@output"))),
+        multispace0
+    )(input)?;
+    // @input, @input() and @input(file) are all accepted,
+    // a quoted file is a path relative to the source, e.g. @input("../first/reach")
+    let (input, file) = opt(delimited(
+        tuple((tag("("), multispace0)),
+        opt(alt((parse_string, parse_symbol))),
+        tuple((multispace0, tag(")"), multispace0))
     ))(input)?;
-    // let (input, io) = alt((
-    //     map(delimited(
-    //         tuple((tag("@input("), multispace0)),
-    //         opt(parse_symbol),
-    //         tuple((multispace0, tag(")"), multispace0))
-    //     ), |symbol| IO::Read(symbol)),
-    //     map(delimited(
-    //         tuple((multispace0, tag("@output("), multispace0)),
-    //         opt(parse_symbol),
-    //         tuple((multispace0, tag(")"), multispace0))
-    //     ), |symbol| IO::Write(symbol)),
-    // ))(input)?;
+    let file = file.flatten();
+    let io = match annotator {
+        "@input" => IO::Read(file),
+        "@output" => IO::Write(file),
+        "@output_bag" => IO::WriteBag(file),
+        _ => unreachable!(),
+    };
     Ok((input, io))
 }
 
-fn parse_atom(input: &str) -> IResult<&str, Atom> {
+pub fn parse_atom(input: &str) -> IResult<&str, Atom> {
     let (input, negation) = opt(
         tuple((tag("Not"), multispace0)
     ))(input)?;
@@ -157,7 +217,7 @@ fn parse_equal(input: &str) -> IResult<&str, Arith> {
     let (input, lhs) = parse_compare(input)?;
     let (input, _) = multispace0(input)?;
     let (input, operator) = opt(alt(
-        (tag("=="), tag("!="))
+        (tag("=="), tag("!="), tag("="))
     ))(input)?;
     let (input, _) = multispace0(input)?;
     match operator {
@@ -165,7 +225,7 @@ fn parse_equal(input: &str) -> IResult<&str, Arith> {
             let (input, rhs) = parse_compare(input)?;
             Ok((input, Arith {
                 operator: match operator {
-                    "==" => Operator::Unifier,
+                    "==" | "=" => Operator::Unifier,
                     "!=" => Operator::Disunifier,
                     _ => unreachable!(),
                 },
@@ -181,6 +241,13 @@ fn parse_compare(input: &str) -> IResult<&str, Arith> {
     let (input, _) = multispace0(input)?;
     let (input, lhs) = parse_plus_minus(input)?;
     let (input, _) = multispace0(input)?;
+    if let Ok((input, constants)) = parse_membership(input) {
+        return Ok((input, Arith {
+            operator: Operator::In(constants),
+            lhs: Some(Box::new(lhs)),
+            rhs: None,
+        }));
+    }
     let (input, operator) = opt(alt(
         (tag("<="), tag(">="), tag("<"), tag(">"))
     ))(input)?;
@@ -204,54 +271,82 @@ fn parse_compare(input: &str) -> IResult<&str, Arith> {
     }
 }
 
+/// the constants of a membership, e.g. `in (a, b, c)`,
+/// `in` is a keyword rather than the start of a predicate such as `index`
+fn parse_membership(input: &str) -> IResult<&str, Vec<Constant>> {
+    let (input, _) = terminated(tag("in"), not(satisfy(|c: char| c.is_alphanumeric() || c == '_')))(input)?;
+    let (input, terms) = verify(parse_term_list, |terms: &[Term]| {
+        terms.iter().all(|term| matches!(term, Term::Constant(_)))
+    })(input)?;
+    let constants = terms.into_iter().filter_map(|term| match term {
+        Term::Constant(constant) => Some(constant),
+        Term::Variable(_) => None,
+    }).collect();
+    Ok((input, constants))
+}
+
+/// a chain of `+` and `-` is left-associative, e.g. `X - Y - Z` is `(X - Y) - Z`
 fn parse_plus_minus(input: &str) -> IResult<&str, Arith> {
     let (input, _) = multispace0(input)?;
-    let (input, lhs) = parse_mul_div(input)?;
-    let (input, _) = multispace0(input)?;
-    let (input, operator) = opt(alt(
-        (tag("+"), tag("-"))
-    ))(input)?;
+    let (input, first) = parse_mul_div(input)?;
+    let operation = pair(
+        delimited(multispace0, alt((tag("+"), tag("-"))), multispace0),
+        parse_mul_div,
+    );
+    let (input, arith) = fold_many0(operation, move || first.clone(), |lhs, (operator, rhs)| Arith {
+        operator: match operator {
+            "+" => Operator::Add,
+            "-" => Operator::Sub,
+            _ => unreachable!(),
+        },
+        lhs: Some(Box::new(lhs)),
+        rhs: Some(Box::new(rhs)),
+    })(input)?;
     let (input, _) = multispace0(input)?;
-    match operator {
-        Some(operator) => {
-            let (input, rhs) = parse_mul_div(input)?;
-            Ok((input, Arith {
-                operator: match operator {
-                    "+" => Operator::Add,
-                    "-" => Operator::Sub,
-                    _ => unreachable!(),
-                },
-                lhs: Some(Box::new(lhs)),
-                rhs: Some(Box::new(rhs)),
-            }))
-        }
-        None => Ok((input, lhs))
-    }
+    Ok((input, arith))
 }
 
+/// a chain of `*`, `/` and `%` is left-associative, e.g. `X / Y * Z` is `(X / Y) * Z`
 fn parse_mul_div(input: &str) -> IResult<&str, Arith> {
     let (input, _) = multispace0(input)?;
-    let (input, lhs) = parse_unary(input)?;
-    let (input, _) = multispace0(input)?;
-    let (input, operator) = opt(alt(
-        (tag("*"), tag("/"))
-    ))(input)?;
-    let (input, _) = multispace0(input)?;
-    match operator {
-        Some(operator) => {
-            let (input, rhs) = parse_unary(input)?;
-            Ok((input, Arith {
-                operator: match operator {
-                    "*" => Operator::Mul,
-                    "/" => Operator::Div,
-                    _ => unreachable!(),
-                },
-                lhs: Some(Box::new(lhs)),
-                rhs: Some(Box::new(rhs)),
-            }))
-        }
-        None => Ok((input, lhs))
+    let (mut input, (mut text, mut arith)) = consumed(parse_unary)(input)?;
+    loop {
+        // `//` and `/*` start a comment rather than a division,
+        // and `%` is a modulo on the line of its lhs when an operand follows it on that line,
+        // but starts a comment on a line of its own or before anything else, e.g. `X > 100 % note`
+        let same_line = !text[text.trim_end().len()..].contains('\n');
+        let operation = opt(alt((
+            preceded(multispace0, alt((tag("*"), terminated(tag("/"), not(alt((tag("/"), tag("*")))))))),
+            verify(preceded(space0, terminated(tag("%"), peek(parse_modulo_operand))), |_: &str| same_line),
+        )))(input)?;
+        let (rest, Some(operator)) = operation else {
+            break;
+        };
+        let (rest, _) = multispace0(rest)?;
+        let (rest, (operand, rhs)) = consumed(parse_unary)(rest)?;
+        arith = Arith {
+            operator: match operator {
+                "*" => Operator::Mul,
+                "/" => Operator::Div,
+                "%" => Operator::Mod,
+                _ => unreachable!(),
+            },
+            lhs: Some(Box::new(arith)),
+            rhs: Some(Box::new(rhs)),
+        };
+        (input, text) = (rest, operand);
     }
+    let (input, _) = multispace0(input)?;
+    Ok((input, arith))
+}
+
+/// the rhs of a modulo on the line of the `%`, a symbol is never a number,
+/// so that `% note` is a comment
+fn parse_modulo_operand(input: &str) -> IResult<&str, Arith> {
+    let (input, _) = pair(space0, not(line_ending))(input)?;
+    verify(parse_unary, |operand: &Arith| {
+        !matches!(operand.operator, Operator::Leaf(Term::Constant(Constant::Symbol(_))))
+    })(input)
 }
 
 fn parse_unary(input: &str) -> IResult<&str, Arith> {
@@ -273,78 +368,157 @@ fn parse_unary(input: &str) -> IResult<&str, Arith> {
                 rhs: Some(Box::new(rhs)),
             }))
         }
-        None => parse_primary(input)
+        None => alt((parse_function, parse_aggregate, parse_primary))(input)
     }
 }
 
-fn parse_primary(input: &str) -> IResult<&str, Arith> {
-    let (input, _) = multispace0(input)?;
-    let (input, parenthesis) = opt(tag("("))(input)?;
-    let (input, _) = multispace0(input)?;
-    let (input, term) = parse_term(input)?;
-    match parenthesis {
-        Some(_) => {
-            let (input, _) = multispace0(input)?;
-            let (input, _) = tag(")")(input)?;
-            Ok((input, Arith {
-                operator: Operator::Leaf(term),
-                lhs: None,
-                rhs: None
-            }))
-        }
-        None => Ok((input, Arith {
-            operator: Operator::Leaf(term),
+/// a built-in function, `abs(E)` of one expression, or `min(E1, E2)` and `max(E1, E2)` of two,
+/// `min(X)` of a single variable is left to the aggregate
+fn parse_function(input: &str) -> IResult<&str, Arith> {
+    let argument = || delimited(multispace0, parse_expr, multispace0);
+    let (input, function) = alt((
+        map(tag("abs"), |_| Function::Abs),
+        map(tag("min"), |_| Function::Min),
+        map(tag("max"), |_| Function::Max),
+    ))(input)?;
+    let (input, _) = pair(multispace0, tag("("))(input)?;
+    let (input, (lhs, rhs)) = match function {
+        Function::Abs => map(argument(), |rhs| (None, rhs))(input)?,
+        _ => map(tuple((argument(), tag(","), argument())), |(lhs, _, rhs)| (Some(Box::new(lhs)), rhs))(input)?,
+    };
+    let (input, _) = pair(tag(")"), multispace0)(input)?;
+    Ok((input, Arith {
+        operator: Operator::Function(function),
+        lhs,
+        rhs: Some(Box::new(rhs)),
+    }))
+}
+
+fn parse_aggregate(input: &str) -> IResult<&str, Arith> {
+    let (input, aggregate) = alt((
+        map(tag("count_distinct"), |_| Aggregate::CountDistinct),
+        map(tag("count"), |_| Aggregate::Count),
+        map(tag("sum"), |_| Aggregate::Sum),
+        map(tag("min"), |_| Aggregate::Min),
+        map(tag("max"), |_| Aggregate::Max),
+        map(tag("avg"), |_| Aggregate::Avg),
+    ))(input)?;
+    let (input, _) = tuple((multispace0, tag("("), multispace0))(input)?;
+    // count takes no argument, the others aggregate a variable
+    let (input, variable) = match aggregate {
+        Aggregate::Count => (input, None),
+        _ => map(parse_variable, Some)(input)?,
+    };
+    let (input, _) = tuple((multispace0, tag(")"), multispace0))(input)?;
+    Ok((input, Arith {
+        operator: Operator::Aggregate(aggregate),
+        lhs: None,
+        rhs: variable.map(|variable| Box::new(Arith {
+            operator: Operator::Leaf(Term::Variable(variable)),
             lhs: None,
             rhs: None
         }))
+    }))
+}
+
+fn parse_primary(input: &str) -> IResult<&str, Arith> {
+    let (input, _) = multispace0(input)?;
+    // a parenthesized expression, e.g. `!(X < Y)` or `(A + B) * C`
+    let (input, parenthesis) = opt(tag("("))(input)?;
+    if parenthesis.is_some() {
+        let (input, arith) = parse_expr(input)?;
+        let (input, _) = multispace0(input)?;
+        let (input, _) = tag(")")(input)?;
+        return Ok((input, arith));
     }
+    let (input, term) = parse_term(input)?;
+    Ok((input, Arith {
+        operator: Operator::Leaf(term),
+        lhs: None,
+        rhs: None
+    }))
 }
 
 fn parse_clause(input: &str) -> IResult<&str, Clause> {
+    // an atom followed by an operator is a call of a built-in function, e.g. `abs(X) < 3`,
+    // `/` and `%` are left out as they may start a comment after an atom
+    let operator = preceded(multispace0, one_of("<>=!+-*&|"));
     let (input, clause) = alt((
-        map(parse_atom, |atom| Clause::Atom(atom)),
-        map(parse_expr, |expr| Clause::Arithmetic(expr)),
+        map(terminated(parse_atom, not(operator)), Clause::Atom),
+        map(parse_expr, Clause::Arithmetic),
     ))(input)?;
     Ok((input, clause))
 }
 
-fn parse_rules(input: &str) -> IResult<&str, Rule> {
+pub fn parse_rules(input: &str) -> IResult<&str, Rule> {
     let (input, annotator) = opt(parse_annotator)(input)?;
     let io = annotator.unwrap_or(IO::Silent);
-    let (input, head) = parse_atom(input)?;
+    let (input, (head, columns)) = match io {
+        IO::Read(_) => parse_declaration(input)?,
+        _ => map(parse_atom, |atom| (atom, Vec::new()))(input)?,
+    };
     let (input, define) = opt(tag(":-"))(input)?;
     let (mut input, _) = multispace0(input)?;
     let mut body = Vec::new();
+    let mut disjuncts = Vec::new();
     if define.is_some() {
-        let (input_inner, clauses) = delimited(
+        // `;` separates the disjuncts of a body, and binds looser than `,`
+        let (input_inner, mut bodies) = delimited(
             multispace0,
-            separated_list1(tuple(
-                (multispace0, tag(","), multispace0)
-            ), parse_clause),
+            separated_list1(
+                tuple((multispace0, tag(";"), multispace0)),
+                separated_list1(tuple(
+                    (multispace0, tag(","), multispace0)
+                ), parse_clause)
+            ),
             multispace0,
         )(input)?;
-        body = clauses;
+        disjuncts = bodies.split_off(1);
+        body = bodies.remove(0);
         input = input_inner;
     }
-    let rule = Rule { io, head, body };
+    // a rule may end with `.`, as facts usually do
+    let (input, _) = opt(tuple((tag("."), multispace0)))(input)?;
+    let rule = Rule { io, head, body, columns, disjuncts };
     Ok((input, rule))
 }
 
-fn parse_comment(input: &str) -> IResult<&str, &str> {
-    let (input, comment) = preceded(
-        tuple((multispace0, tag("%"), multispace0)),
-        take_until("\n")
+/// [`Item`] is a top level statement of a source file.
+pub enum Item {
+    Rule(Rule),
+    /// `#include "common.amo"` splices the rules of another file in place
+    Include(String),
+}
+
+fn parse_include(input: &str) -> IResult<&str, String> {
+    let (input, path) = preceded(
+        tuple((multispace0, tag("#include"), multispace0)),
+        delimited(tag("\""), take_until("\""), tag("\""))
     )(input)?;
     let (input, _) = multispace0(input)?;
-    Ok((input, comment))
+    Ok((input, path.to_string()))
+}
+
+/// a comment is `% ...` or `// ...` up to the end of the line,
+/// or `/* ... */` spanning any number of lines.
+/// `%` on the line of an arithmetic operand is a modulo, see [`parse_mul_div`].
+/// block comments do not nest, the first `*/` closes the comment.
+fn parse_comment(input: &str) -> IResult<&str, &str> {
+    let (input, comment) = preceded(multispace0, alt((
+        preceded(alt((tag("%"), tag("//"))), not_line_ending),
+        delimited(tag("/*"), take_until("*/"), tag("*/")),
+    )))(input)?;
+    let (input, _) = multispace0(input)?;
+    Ok((input, comment.trim()))
 }
 
-pub fn parse_program(input: &str) -> IResult<&str, Program> {
+pub fn parse_program(input: &str) -> IResult<&str, Vec<Item>> {
     let (input, _) = multispace0(input)?;
-    let (input, rules) = many0(alt((
+    let (input, items) = many0(alt((
         map(parse_comment, |_| None),
-        map(parse_rules, Some),
+        map(parse_include, |path| Some(Item::Include(path))),
+        map(parse_rules, |rule| Some(Item::Rule(rule))),
     )))(input)?;
-    let rules = rules.into_iter().flatten().collect();
-    Ok((input, rules))
+    let items = items.into_iter().flatten().collect();
+    Ok((input, items))
 }
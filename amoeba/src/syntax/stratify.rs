@@ -1,14 +1,17 @@
-use std::collections::{HashMap, HashSet};
-use petgraph::{algo, graphmap::DiGraphMap};
+use std::collections::{HashMap, HashSet, VecDeque};
+use petgraph::{algo, graphmap::DiGraphMap, dot::{Dot, Config}};
 
 #[derive(Clone)]
 pub struct Stratum {
+    /// strata in evaluation order, each stratum only depends on itself and the ones before it
     pub strata: Vec<HashSet<String>>,
-    pub levels: HashMap<String, usize>
+    pub levels: HashMap<String, usize>,
+    /// a head depends on each predicate in its body, negated or not
+    pub dependencies: Vec<(String, String, bool)>,
 }
 
 impl Stratum {
-    pub fn new(relations: HashSet<String>, dependencies: HashSet<(&String, &String)>) -> Self {
+    pub fn new(relations: HashSet<String>, dependencies: HashSet<(&String, &String, bool)>) -> Self {
         let mut graph = DiGraphMap::new();
         for node in relations.iter() {
             graph.add_node(node);
@@ -17,20 +20,187 @@ impl Stratum {
             graph.add_edge(edge.0, edge.1, ());
         }
         let scc = algo::kosaraju_scc(&graph);
+        let mut components = HashMap::new();
+        for (i, component) in scc.iter().enumerate() {
+            for node in component {
+                components.insert(*node, i);
+            }
+        }
+        // condense the components into a DAG, an edge points from a component to one it depends on
+        let mut condensed = DiGraphMap::new();
+        for i in 0..scc.len() {
+            condensed.add_node(i);
+        }
+        for (from, to, _) in graph.all_edges() {
+            let (from, to) = (components[from], components[to]);
+            if from != to {
+                condensed.add_edge(from, to, ());
+            }
+        }
+        // dependencies come first, so the topological order is reversed
+        let mut order = algo::toposort(&condensed, None)
+            .expect("Condensed graph should be acyclic");
+        order.reverse();
         let mut strata = Vec::new();
         let mut levels = HashMap::new();
-        for (i, component) in scc.into_iter().enumerate() {
+        for (level, i) in order.into_iter().enumerate() {
             let mut stratum = HashSet::new();
-            for node in component {
+            for node in scc[i].iter() {
                 stratum.insert(node.to_string());
-                levels.insert(node.to_string(), i);
+                levels.insert(node.to_string(), level);
             }
             strata.push(stratum);
         }
-        Self { strata, levels }
+        let mut dependencies = dependencies.into_iter()
+            .map(|(head, body, negation)| (head.clone(), body.clone(), negation))
+            .collect::<Vec<(String, String, bool)>>();
+        dependencies.sort();
+        Self { strata, levels, dependencies }
+    }
+
+    /// whether a predicate is recursive, i.e. depends on itself directly or through the other predicates of its stratum
+    pub fn is_recursive(&self, relation: &str) -> bool {
+        self.levels.get(relation).is_some_and(|level| {
+            self.strata[*level].len() > 1
+                || self.dependencies.iter().any(|(head, body, _)| head == relation && body == relation)
+        })
     }
 
-    pub fn get_level(&self, relation: &String) -> usize {
-        *self.levels.get(relation).expect("relation not found")
+    /// the strata in evaluation order, a line of the predicates of each stratum,
+    /// a recursive predicate is marked as such
+    ///
+    /// ```
+    /// let context = amoeba::syntax::parse("
+    ///     edge(a, b).
+    ///     reach(X, Y) :- edge(X, Y)
+    ///     reach(X, Z) :- edge(X, Y), reach(Y, Z)
+    ///     leaf(X) :- edge(_, X), Not edge(X, _)
+    /// ").unwrap();
+    /// let listing = context.stratum.to_listing();
+    ///
+    /// assert!(listing.contains("reach (recursive)"));
+    /// assert!(listing.find("edge").unwrap() < listing.find("leaf").unwrap());
+    /// ```
+    pub fn to_listing(&self) -> String {
+        let mut lines = Vec::new();
+        for (level, stratum) in self.strata.iter().enumerate() {
+            let mut predicates = stratum.iter().collect::<Vec<&String>>();
+            predicates.sort();
+            let predicates = predicates.into_iter()
+                .map(|predicate| match self.is_recursive(predicate) {
+                    true => format!("{} (recursive)", predicate),
+                    false => predicate.to_string(),
+                })
+                .collect::<Vec<String>>();
+            lines.push(format!("stratum {}: {}", level, predicates.join(", ")));
+        }
+        lines.join("\n")
+    }
+
+    /// the dependency graph in Graphviz DOT, each predicate is labeled with its stratum
+    /// and negated dependencies are dashed
+    pub fn to_dot(&self) -> String {
+        let mut graph = DiGraphMap::new();
+        for stratum in self.strata.iter() {
+            let mut predicates = stratum.iter().collect::<Vec<&String>>();
+            predicates.sort();
+            for predicate in predicates {
+                graph.add_node(predicate.as_str());
+            }
+        }
+        for (head, body, negation) in self.dependencies.iter() {
+            let negated = graph.edge_weight(head.as_str(), body.as_str()).copied().unwrap_or(false);
+            graph.add_edge(head.as_str(), body.as_str(), negated || *negation);
+        }
+        let edge_attributes = |_, (_, _, negation): (&str, &str, &bool)| {
+            if *negation {
+                "style = dashed, label = \"not\"".to_string()
+            } else {
+                String::new()
+            }
+        };
+        let node_attributes = |_, (predicate, _): (&str, &&str)| {
+            format!("label = \"{} (stratum {})\"", predicate, self.levels[predicate])
+        };
+        let dot = Dot::with_attr_getters(
+            &graph,
+            &[Config::EdgeNoLabel, Config::NodeNoLabel],
+            &edge_attributes,
+            &node_attributes
+        );
+        format!("{:?}", dot)
     }
-}
\ No newline at end of file
+
+    /// the predicates a predicate depends on, directly or through other predicates, including itself
+    pub fn dependencies_of(&self, relation: &str) -> HashSet<String> {
+        let mut visited = HashSet::new();
+        let mut queue = vec![relation.to_string()];
+        while let Some(head) = queue.pop() {
+            if !visited.insert(head.clone()) {
+                continue;
+            }
+            self.dependencies.iter()
+                .filter(|(from, _, _)| *from == head)
+                .for_each(|(_, to, _)| queue.push(to.clone()));
+        }
+        visited
+    }
+
+    /// the shortest chain of dependencies from one predicate to another, excluding `from`
+    /// and empty if both are the same, each predicate is paired with whether it is negated
+    /// in the body of the previous one
+    pub fn path(&self, from: &str, to: &str) -> Option<Vec<(String, bool)>> {
+        let mut previous: HashMap<&str, (&str, bool)> = HashMap::new();
+        let mut queue = VecDeque::from([from]);
+        while let Some(head) = queue.pop_front() {
+            if head == to {
+                let mut path = Vec::new();
+                let mut node = to;
+                while node != from {
+                    let (parent, negation) = previous[node];
+                    path.push((node.to_string(), negation));
+                    node = parent;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for (_, body, negation) in self.dependencies.iter().filter(|(dependent, _, _)| dependent == head) {
+                if body != from && !previous.contains_key(body.as_str()) {
+                    previous.insert(body, (head, *negation));
+                    queue.push_back(body);
+                }
+            }
+        }
+        None
+    }
+
+    /// the indexes of the strata grouped by rank, a stratum only depends on strata of lower ranks,
+    /// so that the strata of a rank are independent of each other
+    pub fn ranks(&self) -> Vec<Vec<usize>> {
+        let mut ranks = vec![0; self.strata.len()];
+        for (level, stratum) in self.strata.iter().enumerate() {
+            ranks[level] = self.dependencies.iter()
+                .filter(|(head, body, _)| stratum.contains(head) && !stratum.contains(body))
+                .map(|(_, body, _)| ranks[self.levels[body]] + 1)
+                .max()
+                .unwrap_or(0);
+        }
+        let mut grouped = vec![Vec::new(); ranks.iter().max().map_or(0, |rank| rank + 1)];
+        for (level, rank) in ranks.into_iter().enumerate() {
+            grouped[rank].push(level);
+        }
+        grouped
+    }
+
+    /// the level of the stratum of a predicate, none for a predicate which is not in the dependency graph
+    ///
+    /// ```
+    /// let context = amoeba::syntax::parse("edge(a, b).\nreach(X, Y) :- edge(X, Y)\n").unwrap();
+    ///
+    /// assert!(context.stratum.get_level("edge") < context.stratum.get_level("reach"));
+    /// assert_eq!(context.stratum.get_level("unknown"), None);
+    /// ```
+    pub fn get_level(&self, relation: &str) -> Option<usize> {
+        self.levels.get(relation).copied()
+    }
+}
@@ -0,0 +1,48 @@
+import os
+import sqlite3
+
+
+def main():
+    path = __file__.split('/')[: -1]
+    path = '/'.join(path) + '/aggregate.db'
+    if os.path.exists(path):
+        os.remove(path)
+    conn = sqlite3.connect(path)
+    cursor = conn.cursor()
+    cursor.execute("\
+        CREATE TABLE IF NOT EXISTS edge (\
+            column_0 TEXT NOT NULL,\
+            column_1 TEXT NOT NULL\
+        )\
+    ")
+    edge = [
+        ('a', 'b'),
+        ('a', 'c'),
+        ('a', 'd'),
+        ('b', 'c'),
+        ('c', 'd'),
+    ]
+    for src, tgt in edge:
+        cursor.execute("INSERT INTO edge VALUES (?, ?)", (src, tgt))
+    cursor.execute("\
+        CREATE TABLE IF NOT EXISTS item (\
+            column_0 TEXT NOT NULL,\
+            column_1 INTEGER NOT NULL,\
+            column_2 REAL NOT NULL\
+        )\
+    ")
+    item = [
+        ('fruit', 3, 0.5),
+        ('fruit', 5, 1.25),
+        ('fruit', 1, 2.0),
+        ('tool', 2, 10.5),
+        ('tool', 7, 3.0),
+    ]
+    for group, quantity, weight in item:
+        cursor.execute("INSERT INTO item VALUES (?, ?, ?)", (group, quantity, weight))
+    conn.commit()
+    conn.close()
+
+
+if __name__ == '__main__':
+    main()
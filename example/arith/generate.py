@@ -0,0 +1,41 @@
+import os
+import sqlite3
+NUM_NUMBERS = 200
+
+
+def main():
+    path = __file__.split('/')[: -1]
+    path = '/'.join(path) + '/arith.db'
+    if os.path.exists(path):
+        os.remove(path)
+    conn = sqlite3.connect(path)
+    cursor = conn.cursor()
+    cursor.execute("\
+        CREATE TABLE IF NOT EXISTS num (\
+            column_0 INTEGER NOT NULL\
+        )\
+    ")
+    for i in range(1, NUM_NUMBERS + 1):
+        cursor.execute("INSERT INTO num VALUES (?)", (i,))
+    cursor.execute("\
+        CREATE TABLE IF NOT EXISTS point (\
+            column_0 INTEGER NOT NULL,\
+            column_1 INTEGER NOT NULL\
+        )\
+    ")
+    point = [
+        (1, 1),
+        (1, 2),
+        (2, 1),
+        (3, 3),
+        (4, 7),
+        (-2, -3),
+    ]
+    for x, y in point:
+        cursor.execute("INSERT INTO point VALUES (?, ?)", (x, y))
+    conn.commit()
+    conn.close()
+
+
+if __name__ == '__main__':
+    main()
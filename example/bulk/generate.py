@@ -0,0 +1,22 @@
+import os
+NUM_ROWS = 50000
+
+
+def main():
+    directory = '/'.join(__file__.split('/')[: -1])
+    # every row is distinct, so each of them is counted
+    rows = [(i, 'node{}'.format(i % 1000), i * 0.5) for i in range(NUM_ROWS)]
+    with open(os.path.join(directory, 'rows.csv'), 'w') as file:
+        file.write('id,name,weight\n')
+        for row in rows:
+            file.write('{},{},{}\n'.format(*row))
+    # the same rows written as inline facts
+    with open(os.path.join(directory, 'facts.amo'), 'w') as file:
+        for row in rows:
+            file.write('row({}, {}, {}).\n'.format(*row))
+        file.write('\ntotal(N) :- row(_, _, _), N = count()\n')
+        file.write('\n@output\ntotal(N)\n')
+
+
+if __name__ == '__main__':
+    main()
@@ -0,0 +1,27 @@
+import os
+import sqlite3
+NUM_EDGES = 200
+
+
+def main():
+    path = __file__.split('/')[: -1]
+    path = '/'.join(path) + '/chain.db'
+    if os.path.exists(path):
+        os.remove(path)
+    conn = sqlite3.connect(path)
+    cursor = conn.cursor()
+    cursor.execute("\
+        CREATE TABLE IF NOT EXISTS edge (\
+            column_0 INTEGER NOT NULL,\
+            column_1 INTEGER NOT NULL\
+        )\
+    ")
+    # a chain 0 -> 1 -> ... -> NUM_EDGES
+    for src in range(NUM_EDGES):
+        cursor.execute("INSERT INTO edge VALUES (?, ?)", (src, src + 1))
+    conn.commit()
+    conn.close()
+
+
+if __name__ == '__main__':
+    main()
@@ -0,0 +1,31 @@
+import os
+import sqlite3
+
+
+def create(path, rows):
+    if os.path.exists(path):
+        os.remove(path)
+    conn = sqlite3.connect(path)
+    cursor = conn.cursor()
+    # every column is stored as text, whatever its declared type in the program
+    cursor.execute("\
+        CREATE TABLE IF NOT EXISTS edge (\
+            column_0 TEXT NOT NULL,\
+            column_1 TEXT NOT NULL,\
+            column_2 TEXT NOT NULL\
+        )\
+    ")
+    cursor.executemany("INSERT INTO edge VALUES (?, ?, ?)", rows)
+    conn.commit()
+    conn.close()
+
+
+def main():
+    directory = '/'.join(__file__.split('/')[: -1])
+    create(directory + '/weighted.db', [('1', '2', '0.5'), ('2', '3', '1.5'), ('3', '10', '2')])
+    create(directory + '/invalid.db', [('1', '2', '0.5'), ('2', 'three', '1.5')])
+    create(directory + '/nan.db', [('1', '2', '0.5'), ('2', '3', 'nan')])
+
+
+if __name__ == '__main__':
+    main()
@@ -0,0 +1,33 @@
+import os
+import random
+import sqlite3
+NUM_NODES = 150
+NUM_EDGES = 300
+
+
+def main():
+    path = __file__.split('/')[: -1]
+    path = '/'.join(path) + '/reach.db'
+    for file in [path, path[: -len('.db')] + '.disk.db']:
+        if os.path.exists(file):
+            os.remove(file)
+    conn = sqlite3.connect(path)
+    cursor = conn.cursor()
+    cursor.execute("\
+        CREATE TABLE IF NOT EXISTS edge (\
+            column_0 INTEGER NOT NULL,\
+            column_1 INTEGER NOT NULL\
+        )\
+    ")
+    # a random graph, the same on every run
+    random.seed(42)
+    for _ in range(NUM_EDGES):
+        src = random.randrange(NUM_NODES)
+        dst = random.randrange(NUM_NODES)
+        cursor.execute("INSERT INTO edge VALUES (?, ?)", (src, dst))
+    conn.commit()
+    conn.close()
+
+
+if __name__ == '__main__':
+    main()
@@ -0,0 +1,23 @@
+import os
+import sqlite3
+
+
+def main():
+    path = __file__.split('/')[: -1]
+    path = '/'.join(path) + '/limit.db'
+    if os.path.exists(path):
+        os.remove(path)
+    conn = sqlite3.connect(path)
+    cursor = conn.cursor()
+    cursor.execute("\
+        CREATE TABLE IF NOT EXISTS num (\
+            column_0 INTEGER NOT NULL\
+        )\
+    ")
+    cursor.execute("INSERT INTO num VALUES (?)", (0,))
+    conn.commit()
+    conn.close()
+
+
+if __name__ == '__main__':
+    main()
@@ -0,0 +1,31 @@
+import os
+import sqlite3
+import random
+NUM_NODES = 1000
+NUM_EDGES = 1300
+
+
+def main():
+    path = __file__.split('/')[: -1]
+    path = '/'.join(path) + '/magic.db'
+    if os.path.exists(path):
+        os.remove(path)
+    conn = sqlite3.connect(path)
+    cursor = conn.cursor()
+    cursor.execute("\
+        CREATE TABLE IF NOT EXISTS edge (\
+            column_0 INTEGER NOT NULL,\
+            column_1 INTEGER NOT NULL\
+        )\
+    ")
+    for i in range(NUM_EDGES):
+        # the queried node 0 has a few outgoing edges, so that its answer is not trivially empty
+        src = 0 if i < 5 else random.randint(0, NUM_NODES - 1)
+        tgt = random.randint(0, NUM_NODES - 1)
+        cursor.execute("INSERT INTO edge VALUES (?, ?)", (src, tgt))
+    conn.commit()
+    conn.close()
+
+
+if __name__ == '__main__':
+    main()
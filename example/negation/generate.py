@@ -0,0 +1,42 @@
+import os
+import sqlite3
+
+
+def main():
+    path = __file__.split('/')[: -1]
+    path = '/'.join(path) + '/negation.db'
+    if os.path.exists(path):
+        os.remove(path)
+    conn = sqlite3.connect(path)
+    cursor = conn.cursor()
+    cursor.execute("\
+        CREATE TABLE IF NOT EXISTS edge (\
+            column_0 TEXT NOT NULL,\
+            column_1 TEXT NOT NULL\
+        )\
+    ")
+    edge = [
+        ('a', 'b'),
+        ('b', 'c'),
+        ('c', 'd'),
+    ]
+    for src, tgt in edge:
+        cursor.execute("INSERT INTO edge VALUES (?, ?)", (src, tgt))
+    cursor.execute("\
+        CREATE TABLE IF NOT EXISTS excluded (\
+            column_0 TEXT NOT NULL,\
+            column_1 TEXT NOT NULL\
+        )\
+    ")
+    excluded = [
+        ('a', 'c'),
+        ('b', 'd'),
+    ]
+    for src, tgt in excluded:
+        cursor.execute("INSERT INTO excluded VALUES (?, ?)", (src, tgt))
+    conn.commit()
+    conn.close()
+
+
+if __name__ == '__main__':
+    main()
@@ -0,0 +1,31 @@
+import os
+import sqlite3
+
+
+def main():
+    path = __file__.split('/')[: -1]
+    path = '/'.join(path) + '/selfjoin.db'
+    if os.path.exists(path):
+        os.remove(path)
+    conn = sqlite3.connect(path)
+    cursor = conn.cursor()
+    cursor.execute("\
+        CREATE TABLE IF NOT EXISTS edge (\
+            column_0 TEXT NOT NULL,\
+            column_1 TEXT NOT NULL\
+        )\
+    ")
+    edge = [
+        ('a', 'b'),
+        ('b', 'c'),
+        ('c', 'd'),
+        ('d', 'e'),
+    ]
+    for src, tgt in edge:
+        cursor.execute("INSERT INTO edge VALUES (?, ?)", (src, tgt))
+    conn.commit()
+    conn.close()
+
+
+if __name__ == '__main__':
+    main()
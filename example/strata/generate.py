@@ -0,0 +1,25 @@
+import os
+import sqlite3
+NUM_NUMBERS = 10
+
+
+def main():
+    path = __file__.split('/')[: -1]
+    path = '/'.join(path) + '/strata.db'
+    if os.path.exists(path):
+        os.remove(path)
+    conn = sqlite3.connect(path)
+    cursor = conn.cursor()
+    cursor.execute("\
+        CREATE TABLE IF NOT EXISTS num (\
+            column_0 INTEGER NOT NULL\
+        )\
+    ")
+    for i in range(1, NUM_NUMBERS + 1):
+        cursor.execute("INSERT INTO num VALUES (?)", (i,))
+    conn.commit()
+    conn.close()
+
+
+if __name__ == '__main__':
+    main()
@@ -0,0 +1,30 @@
+import os
+import sqlite3
+
+
+def main():
+    path = __file__.split('/')[: -1]
+    path = '/'.join(path) + '/types.db'
+    if os.path.exists(path):
+        os.remove(path)
+    conn = sqlite3.connect(path)
+    cursor = conn.cursor()
+    cursor.execute("\
+        CREATE TABLE IF NOT EXISTS user (\
+            column_0 TEXT NOT NULL,\
+            column_1 BOOLEAN NOT NULL\
+        )\
+    ")
+    user = [
+        ('alice', True),
+        ('bob', False),
+        ('carol', True),
+    ]
+    for name, active in user:
+        cursor.execute("INSERT INTO user VALUES (?, ?)", (name, active))
+    conn.commit()
+    conn.close()
+
+
+if __name__ == '__main__':
+    main()
@@ -0,0 +1,32 @@
+python ./example/aggregate/generate.py
+cargo run --release -- --source ./example/aggregate/aggregate.amo --verbose
+# expected to fail with a stratification error
+cargo run --release -- --source ./example/aggregate/recursive.amo
+# expected to fail with a type error
+cargo run --release -- --source ./example/aggregate/symbol.amo
+# the column of an average is created as REAL
+cargo run --release -- --source ./example/aggregate/aggregate.amo --explain
+# expected to fail with a stratification error
+cargo run --release -- --source ./example/aggregate/average.amo
+# a distinct count counts each value once, unlike a plain count of the body tuples
+cargo run --release -- --source ./example/aggregate/distinct.amo --no-writeback --format json > ./example/aggregate/distinct.json
+python - <<'END'
+import json
+result = json.load(open('./example/aggregate/distinct.json'))
+counts = lambda name: {row['column_0']: row['column_1'] for row in result[name]}
+assert counts('visits') == {'ann': 3, 'bob': 3, 'cid': 1}, 'expected the visits of each person'
+assert counts('cities') == {'ann': 2, 'bob': 1, 'cid': 1}, 'expected the distinct cities of each person'
+END
+# expected to fail with a stratification error
+cargo run --release -- --source ./example/aggregate/distinct_recursive.amo --no-writeback
+# a comparison of an aggregate keeps the groups passing it, as a HAVING clause
+cargo run --release -- --source ./example/aggregate/having.amo --no-writeback --format json > ./example/aggregate/having.json
+python - <<'END'
+import json
+result = json.load(open('./example/aggregate/having.json'))
+rows = lambda name: sorted(tuple(row.values()) for row in result[name])
+assert rows('popular') == [('a',)], 'expected the groups of more than two items'
+assert rows('heavy') == [('a', 6), ('c', 11)], 'expected the groups weighing more than 4'
+assert rows('several') == [('a',), ('c',)], 'expected the groups of two items heavier than 1'
+END
+cargo run --release -- --source ./example/aggregate/having.amo --explain | grep -q 'HAVING (COUNT(\*) > 2)' || exit 1
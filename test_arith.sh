@@ -0,0 +1,30 @@
+python ./example/arith/generate.py
+cargo run --release -- --source ./example/arith/arith.amo --verbose
+# floats written with an exponent
+cargo run --release -- --source ./example/arith/exponent.amo --no-writeback
+# expected to fail, 1.0e400 overflows a float
+cargo run --release -- --source ./example/arith/overflow.amo --no-writeback
+# chained operators are left-associative, and * / % bind tighter than + -
+cargo run --release -- --source ./example/arith/precedence.amo --no-writeback --format json > ./example/arith/precedence.json
+python - <<'END'
+import json
+results = json.load(open('./example/arith/precedence.json'))
+expected = {'difference': [86, 94, 104], 'quotient': [5, 20, 2], 'mixed': [58, 2, 80]}
+for query, values in expected.items():
+    row = results[query][0]
+    assert [row['column_{}'.format(i)] for i in range(3)] == values, '{} is {}'.format(query, row)
+END
+# nested parentheses, and the built-in functions abs, min and max
+cargo run --release -- --source ./example/arith/functions.amo --no-writeback --format json > ./example/arith/functions.json
+python - <<'END'
+import json
+results = json.load(open('./example/arith/functions.json'))
+rows = lambda query: sorted(tuple(row.values()) for row in results[query])
+assert rows('nested') == [(1, 5, 15), (3, 3, 3), (7, 2, -18)], rows('nested')
+assert rows('distance') == [(1, 5, 4), (3, 3, 0), (7, 2, 5)], rows('distance')
+assert rows('far') == [(1, 5), (7, 2)], rows('far')
+assert rows('bounds') == [(1, 5, 1, 5.0), (3, 3, 3, 3.5), (7, 2, 4, 7.5)], rows('bounds')
+assert rows('least') == [(1,)], rows('least')
+END
+# expected to fail, abs of a symbol
+cargo run --release -- --source ./example/arith/abs_symbol.amo --no-writeback
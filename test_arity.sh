@@ -0,0 +1,12 @@
+# expected to fail with an arity mismatch between a head and a body
+cargo run --release -- --source ./example/arity/head.amo > ./example/arity/head.txt
+# expected to fail with an arity mismatch between an edb and a body
+cargo run --release -- --source ./example/arity/edb.amo > ./example/arity/edb.txt
+python - <<'END'
+for name, predicate in [('head', 'path'), ('edb', 'edge')]:
+    output = open('./example/arity/{}.txt'.format(name)).read()
+    print(output)
+    error = 'ERROR: Predicate {} is used with 3 terms, but 2 are expected'.format(predicate)
+    assert error in output, 'expected {} to be reported'.format(error)
+    assert 'QUERY' not in output, 'expected nothing to be evaluated'
+END
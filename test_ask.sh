@@ -0,0 +1,20 @@
+# --ask prints the tuples of an evaluated predicate matching an atom, its constants and repeated variables filter the rows
+cargo run --release -- --source ./example/ask/ask.amo --no-writeback --ask 'reachable(a, Y)' > ./example/ask/source.txt
+cargo run --release -- --source ./example/ask/ask.amo --no-writeback --ask 'label(X, Y, Y)' > ./example/ask/repeated.txt
+# a wildcard `_` matches any value, so the bound first column is the only filter
+cargo run --release -- --source ./example/ask/ask.amo --no-writeback --ask 'edge(c, _)' > ./example/ask/successors.txt
+cargo run --release -- --source ./example/ask/ask.amo --no-writeback --ask 'edge(_, _)' > ./example/ask/edges.txt
+python - <<'END'
+def answer(path):
+    lines = open(path).read().splitlines()
+    start = [i for i, line in enumerate(lines) if line.startswith('ASK: ')][0]
+    return lines[start + 1:-1]
+assert sorted(answer('./example/ask/source.txt')) == ['a, a', 'a, b', 'a, c', 'a, d'], 'expected every node reachable from a'
+assert sorted(answer('./example/ask/repeated.txt')) == ['a, 1, 1', 'c, 2, 2'], 'expected labels with equal numbers'
+assert sorted(answer('./example/ask/successors.txt')) == ['c, a', 'c, d'], 'expected every edge from c'
+assert len(answer('./example/ask/edges.txt')) == 4, 'expected every edge, as two wildcards are not joined'
+END
+# expected to fail, a view is not materialized
+cargo run --release -- --source ./example/ask/ask.amo --no-writeback --ask 'cyclic(X)'
+# expected to fail, reachable has two columns
+cargo run --release -- --source ./example/ask/ask.amo --no-writeback --ask 'reachable(a)'
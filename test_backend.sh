@@ -0,0 +1,15 @@
+# the answers of the sqlite and the in-memory backend are the same
+cargo run --release -- --source ./example/backend/backend.amo --no-writeback --format json > ./example/backend/sqlite.json
+cargo run --release -- --source ./example/backend/backend.amo --no-writeback --format json --backend memory > ./example/backend/memory.json
+python - <<'END'
+import json
+def load(path):
+    results = json.load(open(path))
+    return {query: sorted(json.dumps(entity, sort_keys=True) for entity in entities) for query, entities in results.items()}
+sqlite = load('./example/backend/sqlite.json')
+memory = load('./example/backend/memory.json')
+for query in sorted(sqlite):
+    print('QUERY: {}'.format(query))
+    print('COUNT: {} (sqlite), {} (memory)'.format(len(sqlite[query]), len(memory[query])))
+assert sqlite == memory, 'the backends give different answers'
+END
@@ -0,0 +1,18 @@
+# @output_bag gives each tuple with the number of its derivations, @output gives it once
+cargo run --release -- --source ./example/bag/bag.amo --no-writeback
+python - <<'END'
+import csv
+def read(name):
+    return [tuple(row) for row in csv.reader(open('./example/bag/{}.csv'.format(name)))]
+assert read('two_set') == [('column_0', 'column_1'), ('a', 'd')], 'expected two(a, d) once'
+assert read('two_bag') == [('column_0', 'column_1', 'multiplicity'), ('a', 'd', '2')], 'expected two(a, d) twice'
+reach = read('reach_bag')
+assert reach[0] == ('column_0', 'column_1', 'multiplicity')
+assert sorted(reach[1:]) == [('a', 'b', '1'), ('a', 'c', '1'), ('a', 'd', '3'), ('b', 'd', '1'), ('c', 'd', '1')]
+assert sorted(read('degree')[1:]) == [('a', '3'), ('b', '1'), ('c', '1')], 'expected the out-degree of each node'
+END
+cargo run --release -- --source ./example/bag/bag.amo --no-writeback --format json
+python - <<'END'
+import json
+assert json.load(open('./example/bag/two_bag.json')) == [{'column_0': 'a', 'column_1': 'd', 'multiplicity': 2}]
+END
@@ -0,0 +1,15 @@
+# booleans are written as 1 and 0 in the generated SQL, and as true and false in the output
+cargo run --release -- --source ./example/boolean/boolean.amo --no-writeback --explain > ./example/boolean/explain.sql
+cargo run --release -- --source ./example/boolean/boolean.amo --no-writeback --format json > ./example/boolean/boolean.json
+python - <<'END'
+import json
+explain = open('./example/boolean/explain.sql').read()
+statements = [line for line in explain.splitlines() if not line.startswith('--')]
+assert not any('true' in line or 'false' in line for line in statements), 'expected no boolean literal in the SQL'
+results = json.load(open('./example/boolean/boolean.json'))
+names = lambda query: sorted(row['column_0'] for row in results[query])
+assert names('active') == ['alice', 'carol']
+assert names('inactive') == ['bob', 'dave']
+assert names('checked') == ['alice', 'carol'] and all(row['column_1'] is True for row in results['checked'])
+assert names('user') == ['bob', 'dave'] and all(row['column_1'] is False for row in results['user'])
+END
@@ -0,0 +1,11 @@
+# 50000 rows loaded from a csv file and from inline facts
+python ./example/bulk/generate.py
+cargo run --release -- --source ./example/bulk/csv.amo --no-writeback --format json > ./example/bulk/csv.json
+cargo run --release -- --source ./example/bulk/facts.amo --no-writeback --format json > ./example/bulk/facts.json
+python - <<'END'
+import json
+for source in ['csv', 'facts']:
+    total = json.load(open('./example/bulk/{}.json'.format(source)))['total']
+    print('{}: {}'.format(source, total))
+    assert total == [{'column_0': 50000}], 'expected 50000 rows loaded from {}'.format(source)
+END
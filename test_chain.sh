@@ -0,0 +1,15 @@
+# a micro-benchmark of a recursive rule over many iterations, whose results match the memory backend
+python ./example/chain/generate.py
+cargo run --release -- --source ./example/chain/chain.amo --no-writeback --stats --bench > ./example/chain/stdout.txt
+mv ./example/chain/path.csv ./example/chain/sqlite.csv
+cargo run --release -- --source ./example/chain/chain.amo --no-writeback --backend memory --bench
+python - <<'END'
+lines = open('./example/chain/stdout.txt').read().splitlines()
+print(lines[-1])
+path = [line.split(None, 2) for line in lines if line.startswith('path ')][0]
+assert path[1] == '200', 'expected an iteration per edge of the chain'
+sqlite = open('./example/chain/sqlite.csv').read().splitlines()
+memory = open('./example/chain/path.csv').read().splitlines()
+assert len(sqlite) == 1 + 200 * 201 // 2, 'expected every pair of the chain in order'
+assert sorted(sqlite) == sorted(memory), 'expected the backends to derive the same paths'
+END
@@ -0,0 +1,37 @@
+# --check reports every problem of a program and its database, and leaves the database untouched
+python - <<'END'
+import sqlite3, os
+if os.path.exists('./example/check/check.db'):
+    os.remove('./example/check/check.db')
+database = sqlite3.connect('./example/check/check.db')
+database.execute('CREATE TABLE edge (column_0 INTEGER, column_1 INTEGER)')
+database.execute('CREATE TABLE weight (column_0 INTEGER, column_1 REAL, column_2 REAL)')
+database.executemany('INSERT INTO edge VALUES (?, ?)', [(1, 2), (2, 3)])
+database.commit()
+END
+cp ./example/check/check.db ./example/check/before.db
+cargo run --release -- --source ./example/check/check.amo --check > ./example/check/stdout.txt
+cargo run --release -- --source ./example/check/reach.amo --db ./example/check/check.db --check >> ./example/check/stdout.txt
+python - <<'END'
+output = open('./example/check/stdout.txt').read()
+print(output)
+for error in [
+    'Column 1 of label is declared as `string`',
+    'colors.csv',
+    'EDB label is not present in database',
+    'EDB node is not present in database',
+    'Table weight has 3 columns, but 2 are declared',
+    'CHECK: ./example/check/reach.amo is ready to be evaluated',
+]:
+    assert error in output, 'expected {} to be reported'.format(error)
+assert output.count('ERROR') == 5, 'expected exactly five problems'
+assert 'QUERY' not in output, 'expected nothing to be evaluated'
+assert open('./example/check/check.db', 'rb').read() == open('./example/check/before.db', 'rb').read(), 'expected the database to be unchanged'
+END
+# --check exits with 1 when it reports a problem, and with 0 when the program is ready to be evaluated
+cargo run --release -- --source ./example/check/check.amo --check > /dev/null; echo $? > ./example/check/status.txt
+cargo run --release -- --source ./example/check/reach.amo --db ./example/check/check.db --check > /dev/null; echo $? >> ./example/check/status.txt
+python - <<'END'
+status = open('./example/check/status.txt').read().split()
+assert status == ['1', '0'], 'expected exit codes 1 and 0, found {}'.format(status)
+END
@@ -1,2 +1,15 @@
 python ./example/closure/generate.py
-cargo run --release -- --source ./example/closure/closure.amo --verbose
+cargo run --release -- --source ./example/closure/closure.amo --verbose > ./example/closure/verbose.txt
+# --verbose prints the records logged by the engine, each statement and each iteration of path
+python - <<'END'
+import re
+output = open('./example/closure/verbose.txt').read()
+output = re.sub(r'\x1b\[[0-9;]*m', '', output)
+assert re.search(r'^EXECUTE: INSERT OR IGNORE INTO path', output, re.MULTILINE), 'expected the statements of path'
+iterations = [int(i) for i in re.findall(r'^ITERATE: path\((\d+)\)$', output, re.MULTILINE)]
+assert iterations and iterations == list(range(len(iterations))), 'expected the iterations of path in order'
+assert re.search(r'^COMMIT: path\(0\)$', output, re.MULTILINE), 'expected the first iteration to be committed'
+fixpoint = re.findall(r'^FIXPOINT: path\((\d+)\)$', output, re.MULTILINE)
+assert fixpoint == [str(iterations[-1])], 'expected a fixpoint at the last iteration, found {}'.format(fixpoint)
+assert 'QUERY: path(X, Y)' in output
+END
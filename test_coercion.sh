@@ -0,0 +1,20 @@
+# the columns of an edb stored as text are coerced to the declared types when loaded
+python ./example/coercion/generate.py
+cargo run --release -- --source ./example/coercion/weighted.amo --no-writeback --format json > ./example/coercion/weighted.json
+python - <<'END'
+import json
+result = json.load(open('./example/coercion/weighted.json'))
+rows = lambda name: sorted(tuple(row.values()) for row in result[name])
+assert rows('path') == [(1, 2), (1, 3), (1, 10), (2, 3), (2, 10), (3, 10)], 'expected the closure over integers'
+assert rows('far') == [(1, 3), (1, 10), (2, 3), (2, 10), (3, 10)], 'expected the integers compared as numbers'
+assert rows('heavy') == [(2, 3, 1.5), (3, 10, 2.0)], 'expected the weights compared as floats'
+END
+# expected to fail, naming the row of a text which is not an integer
+cargo run --release -- --source ./example/coercion/invalid.amo --no-writeback
+# expected to fail, naming the row of a text which is NaN rather than a float
+cargo run --release -- --source ./example/coercion/nan.amo --no-writeback > ./example/coercion/nan.txt
+python - <<'END'
+output = open('./example/coercion/nan.txt').read()
+print(output)
+assert 'Row 2 of edge cannot be coerced to the declared types' in output, 'expected the row of nan to be reported'
+END
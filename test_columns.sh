@@ -0,0 +1,8 @@
+# named columns are used as csv headers, and as json keys
+cargo run --release -- --source ./example/columns/columns.amo --no-writeback
+cargo run --release -- --source ./example/columns/columns.amo --no-writeback --format json
+python - <<'END'
+headers = {name: open('./example/columns/{}.csv'.format(name)).readline().strip() for name in ['everyone', 'liked', 'adults']}
+print(headers)
+assert headers == {'everyone': 'name,age', 'liked': 'who,column_1', 'adults': 'column_0'}, 'unexpected csv headers'
+END
@@ -0,0 +1,17 @@
+cargo run --release -- --source ./example/comment/comment.amo --no-writeback > ./example/comment/comment.txt
+python - <<'END'
+output = open('./example/comment/comment.txt').read()
+print(output)
+assert 'ERROR' not in output, 'expected comments to be skipped'
+counts = {block.split('\n')[0]: block.split('COUNT: ')[1].split('\n')[0] for block in output.split('QUERY: ')[1:]}
+assert counts == {'path(X, Y)': '3', 'link(X, Y)': '1', 'half(X, Y)': '2'}, 'unexpected counts {}'.format(counts)
+assert 'a, http://example.org' in output, 'expected a quoted // to be kept'
+END
+# expected to fail with a parsing error at the unclosed block comment
+cargo run --release -- --source ./example/comment/unclosed.amo --no-writeback > ./example/comment/unclosed.txt
+python - <<'END'
+output = open('./example/comment/unclosed.txt').read()
+print(output)
+assert 'ERROR: ./example/comment/unclosed.amo: Parsing error at line 3, column 1' in output, 'expected the unclosed block comment to be reported'
+assert 'QUERY' not in output, 'expected nothing to be evaluated'
+END
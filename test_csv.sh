@@ -0,0 +1,8 @@
+cargo run --release -- --source ./example/csv/csv.amo --verbose
+# expected to fail, a NaN in the float column of weights.csv is reported as an invalid row
+cargo run --release -- --source ./example/csv/nan.amo --no-writeback > ./example/csv/nan.txt
+python - <<'END'
+output = open('./example/csv/nan.txt').read()
+print(output)
+assert 'weights.csv:2: `NaN` is not a valid Float' in output, 'expected the second row to be rejected'
+END
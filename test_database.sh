@@ -0,0 +1,21 @@
+# the database given by --db lives apart from the source, and is written back in place
+mkdir -p ./example/database/data
+python - <<'END'
+import sqlite3, os
+path = './example/database/data/graph.db'
+if os.path.exists(path):
+    os.remove(path)
+connection = sqlite3.connect(path)
+connection.execute('CREATE TABLE edge (column_0 TEXT, column_1 TEXT)')
+connection.executemany('INSERT INTO edge VALUES (?, ?)', [('a', 'b'), ('b', 'c'), ('c', 'd')])
+connection.commit()
+END
+cargo run --release -- --source ./example/database/program/reach.amo --db ./example/database/data/graph.db
+python - <<'END'
+import sqlite3, os
+assert not os.path.exists('./example/database/program/reach.db'), 'expected no database beside the source'
+reach = sqlite3.connect('./example/database/data/graph.db').execute("SELECT * FROM reach WHERE column_0 = 'a'").fetchall()
+assert sorted(reach) == [('a', 'b'), ('a', 'c'), ('a', 'd')], 'expected reach to be written back to graph.db'
+END
+# expected to fail, reach.db beside the source is missing
+cargo run --release -- --source ./example/database/program/reach.amo --no-writeback
@@ -0,0 +1 @@
+cargo run --release -- --source ./example/disequality/disequality.amo --no-writeback
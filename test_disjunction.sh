@@ -0,0 +1,19 @@
+# a disjunctive rule derives the union of its disjuncts
+cargo run --release -- --source ./example/disjunction/disjunction.amo --no-writeback --format json > ./example/disjunction/disjunction.json
+python - <<'END'
+import json
+results = json.load(open('./example/disjunction/disjunction.json'))
+pairs = lambda query: sorted((row['column_0'], row['column_1']) for row in results[query])
+assert pairs('knows') == [('alice', 'bob'), ('bob', 'carol'), ('carol', 'dave'), ('erin', 'frank')]
+assert pairs('related') == [('alice', 'carol'), ('carol', 'dave')]
+END
+# the disjuncts of a recursive rule reach a fixpoint together, on either backend
+cargo run --release -- --source ./example/disjunction/recursive.amo --no-writeback --format json > ./example/disjunction/recursive.json
+cargo run --release -- --source ./example/disjunction/recursive.amo --no-writeback --format json --backend memory > ./example/disjunction/recursive_memory.json
+python - <<'END'
+import json
+for path in ['./example/disjunction/recursive.json', './example/disjunction/recursive_memory.json']:
+    results = json.load(open(path))
+    pairs = sorted((row['column_0'], row['column_1']) for row in results['p'])
+    assert pairs == [('a', 'b'), ('a', 'c'), ('a', 'd'), ('c', 'd')], 'expected (a, d) through e, f and e in {}, found {}'.format(path, pairs)
+END
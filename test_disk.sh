@@ -0,0 +1,21 @@
+# a disk-backed idb derives the same tuples as an idb held in memory
+python ./example/disk/generate.py
+cargo run --release -- --source ./example/disk/reach.amo --no-writeback --format json --sorted > ./example/disk/memory.json
+mv ./example/disk/reach.json ./example/disk/reach_memory.json
+cargo run --release -- --source ./example/disk/reach.amo --no-writeback --format json --sorted --disk reach > ./example/disk/disk.json
+cargo run --release -- --source ./example/disk/reach.amo --no-writeback --disk reach --backend memory --stats
+python - <<'END'
+import json, sqlite3
+memory = json.load(open('./example/disk/reach_memory.json'))
+disk = json.load(open('./example/disk/reach.json'))
+assert len(memory) > 1000, 'expected a moderate closure, found {} tuples'.format(len(memory))
+assert memory == disk, 'expected the disk-backed idb to derive the same tuples'
+assert json.load(open('./example/disk/memory.json')) == json.load(open('./example/disk/disk.json'))
+# the table of reach is on disk, and neither its scratch relations nor the edbs are
+database = sqlite3.connect('./example/disk/reach.disk.db')
+tables = [row[0] for row in database.execute("SELECT name FROM sqlite_master WHERE type = 'table'")]
+assert tables == ['reach'], 'expected only reach on disk, found {}'.format(tables)
+assert database.execute('SELECT COUNT(*) FROM reach').fetchone()[0] == len(disk)
+END
+# expected to fail, edge is not an idb
+cargo run --release -- --source ./example/disk/reach.amo --no-writeback --disk edge
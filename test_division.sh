@@ -0,0 +1,3 @@
+cargo run --release -- --source ./example/division/division.amo --no-writeback
+# expected to fail with a division by zero
+cargo run --release -- --source ./example/division/zero.amo --no-writeback
@@ -0,0 +1,9 @@
+# duplicate tuples of an edb are warned about, without changing the derived tuples
+cargo run --release -- --source ./example/duplicate/edges.amo --no-writeback --warn-duplicate-edb 2> ./example/duplicate/warnings.txt > ./example/duplicate/output.txt
+python - <<'END'
+warnings = open('./example/duplicate/warnings.txt').read().splitlines()
+output = open('./example/duplicate/output.txt').read().splitlines()
+print('\n'.join(warnings))
+assert warnings == ['WARNING: EDB edge has 3 duplicate tuples'], 'expected 3 duplicate tuples of edge, and none of node'
+assert 'COUNT: 6' in output, 'expected the closure of the distinct edges'
+END
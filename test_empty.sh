@@ -0,0 +1,8 @@
+# a query over a predicate deriving nothing prints no tuple, with a warning for a predicate no rule derives
+cargo run --release -- --source ./example/empty/empty.amo --no-writeback --format json > ./example/empty/empty.json
+python - <<'END'
+import json
+result = json.load(open('./example/empty/empty.json'))
+assert result == {'far': [], 'loop': [], 'nothing': []}, 'expected every query to have no result'
+END
+cargo run --release -- --source ./example/empty/empty.amo --no-writeback
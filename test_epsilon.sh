@@ -0,0 +1,16 @@
+# float equality is exact by default, and up to a tolerance with --epsilon, integers are always compared exactly
+cargo run --release -- --source ./example/epsilon/epsilon.amo --no-writeback --format json > ./example/epsilon/exact.json
+cargo run --release -- --source ./example/epsilon/epsilon.amo --no-writeback --format json --epsilon 1e-9 > ./example/epsilon/epsilon.json
+python - <<'END'
+import json
+exact = json.load(open('./example/epsilon/exact.json'))
+epsilon = json.load(open('./example/epsilon/epsilon.json'))
+assert exact['near'] == [{'column_0': 'b'}], 'expected 0.1 + 0.2 to differ from 0.3 exactly'
+assert sorted(row['column_0'] for row in epsilon['near']) == ['a', 'b'], 'expected 0.1 + 0.2 to equal 0.3 up to 1e-9'
+assert exact['three'] == epsilon['three'] == [{'column_0': 'a'}]
+assert exact['far'] == [{'column_0': 'a'}], 'expected 0.1 + 0.2 to differ from 0.3 exactly'
+assert epsilon['far'] == [], 'expected 0.1 + 0.2 not to differ from 0.3 up to 1e-9'
+END
+cargo run --release -- --source ./example/epsilon/epsilon.amo --no-writeback --explain --epsilon 1e-9
+# expected to fail, a tolerance is non-negative
+cargo run --release -- --source ./example/epsilon/epsilon.amo --no-writeback --epsilon=-1
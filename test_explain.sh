@@ -0,0 +1,6 @@
+python ./example/closure/generate.py
+output=$(cargo run --release -- --source ./example/closure/closure.amo --explain)
+echo "$output"
+# the recursive rule should join edge with the delta of path
+echo "$output" | grep -q "JOIN delta_path AS b1 ON b0.column_1 = b1.column_0" || exit 1
+echo "OK"
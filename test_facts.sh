@@ -0,0 +1,11 @@
+cargo run --release -- --source ./example/facts/facts.amo --verbose --no-writeback
+cargo run --release -- --source ./example/facts/union.amo --verbose --no-writeback
+# expected to fail, a rule without a body has a variable in its head
+cargo run --release -- --source ./example/facts/unbound.amo --no-writeback
+cargo run --release -- --source ./example/facts/typed.amo --format json --no-writeback > example/facts/typed.json
+python - <<'PY'
+import json
+assert json.load(open("example/facts/typed.json"))["heavy"] == [{"column_0": "b", "column_1": 3.0}]
+PY
+# expected to fail, a fact gives the int column of reading a float
+cargo run --release -- --source ./example/facts/mistyped.amo --no-writeback
@@ -0,0 +1,14 @@
+# a comparison assigned to a variable binds it to whether the comparison holds,
+# which is stored as 1 or 0 in a boolean column
+cargo run --release -- --source ./example/flag/flag.amo --format json > ./example/flag/flag.json
+python - <<'END'
+import json, sqlite3
+result = json.load(open('./example/flag/flag.json'))
+flags = lambda name: {row['column_0']: row['column_1'] for row in result[name]}
+assert flags('large') == {1: False, 5: False, 11: True, 20: True}, 'expected the numbers greater than 10'
+assert flags('middle') == {1: False, 5: True, 11: True, 20: False}, 'expected the numbers between 5 and 11'
+assert flags('listed') == {1: True, 5: False, 11: False, 20: True}, 'expected the numbers in (1, 20)'
+database = sqlite3.connect('./example/flag/flag.db')
+stored = database.execute('SELECT column_0, column_1, typeof(column_1) FROM large ORDER BY column_0').fetchall()
+assert stored == [(1, 0, 'integer'), (5, 0, 'integer'), (11, 1, 'integer'), (20, 1, 'integer')], 'expected 0 and 1 in the column'
+END
@@ -0,0 +1,2 @@
+cargo run --release -- --source ./example/generation/generation.amo --no-writeback
+cargo run --release -- --source ./example/generation/generation.amo --no-writeback --backend memory
@@ -0,0 +1,9 @@
+python ./example/negation/generate.py
+cargo run --release -- --source ./example/negation/negation.amo --no-writeback --dump-graph ./example/negation/negation.dot
+cat ./example/negation/negation.dot
+# every predicate is labeled with its stratum, and both negated subgoals are dashed
+for predicate in edge excluded reachable allowed node unreachable; do
+    grep -q "label = \"$predicate (stratum [0-9]*)\"" ./example/negation/negation.dot || exit 1
+done
+[ "$(grep -c 'style = dashed, label = "not"' ./example/negation/negation.dot)" = "2" ] || exit 1
+echo "OK"
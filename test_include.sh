@@ -0,0 +1,6 @@
+python ./example/include/generate.py
+cargo run --release -- --source ./example/include/include.amo --verbose
+# expected to fail with a duplicated predicate
+cargo run --release -- --source ./example/include/duplicate.amo
+# expected to fail with an include cycle
+cargo run --release -- --source ./example/include/cycle_a.amo
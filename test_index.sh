@@ -0,0 +1,14 @@
+python ./example/closure/generate.py
+cargo run --release -- --source ./example/closure/closure.amo
+# scanning the small delta side, each edge should be searched through the index on its join column
+plan=$(sqlite3 ./example/closure/closure.db "EXPLAIN QUERY PLAN SELECT b0.column_0, b1.column_1 FROM path AS b1 CROSS JOIN edge AS b0 ON b0.column_1 = b1.column_0")
+echo "$plan"
+if echo "$plan" | grep -q "SEARCH b0 USING .*INDEX index_edge_1"; then echo "INDEXED"; else echo "SCANNED"; exit 1; fi
+# the plan of the recursive rule of path, as chosen by SQLite after the indexes are created, searches an index
+cargo run --release -- --source ./example/closure/closure.amo --no-writeback --query-plan > ./example/closure/plan.txt
+python - <<'END'
+output = open('./example/closure/plan.txt').read().splitlines()
+plan = output[output.index('PLAN: path by rule 2'):]
+print('\n'.join(plan))
+assert any('USING INDEX index_' in row for row in plan), 'expected a join of path through an index'
+END
@@ -0,0 +1,11 @@
+# a view joins the materialized idbs of its body, once all of them are evaluated
+cargo run --release -- --source ./example/join_view/join.amo --no-writeback --format json > ./example/join_view/sqlite.json
+cargo run --release -- --source ./example/join_view/join.amo --no-writeback --format json --backend memory > ./example/join_view/memory.json
+python - <<'END'
+import json
+for backend in ['sqlite', 'memory']:
+    result = json.load(open('./example/join_view/{}.json'.format(backend)))
+    rows = lambda name: sorted((row['column_0'], row['column_1']) for row in result[name])
+    assert rows('trip') == [('a', 'x'), ('a', 'y'), ('a', 'z'), ('b', 'y'), ('c', 'y')], 'expected drive joined with ride'
+    assert rows('twice') == [('a', 'c'), ('a', 'd'), ('b', 'd')], 'expected drive joined with itself'
+END
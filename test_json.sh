@@ -0,0 +1,9 @@
+output=$(cargo run --release -- --source ./example/facts/facts.amo --no-writeback --format json)
+echo "$output"
+# symbols are strings, floats are numbers
+echo "$output" | grep -q '"column_0": "a"' || exit 1
+echo "$output" | grep -q '"column_1": 1.0' || exit 1
+# the counted degree is an integer column, serialized as a number rather than a quoted string
+output=$(cargo run --release -- --source ./example/aggregate/aggregate.amo --no-writeback --format json)
+echo "$output"
+echo "$output" | grep -qE '"column_1": [0-9]+,?$' || exit 1
@@ -0,0 +1,13 @@
+# an edb annotated with @input(edges) is loaded from edges.json, as there is no edges.csv,
+# whose records are objects keyed by the column names, or arrays of values
+cargo run --release -- --source ./example/jsoninput/reach.amo --no-writeback --format json > ./example/jsoninput/result.json
+python - <<'END'
+import json
+result = json.load(open('./example/jsoninput/result.json'))
+rows = lambda name: sorted((row['column_0'], row['column_1']) for row in result[name])
+expected = [('a', 'b'), ('a', 'c'), ('a', 'd'), ('b', 'c'), ('b', 'd'), ('c', 'd')]
+assert rows('reachable') == expected, 'expected the closure of the edges in edges.json'
+assert rows('heavy') == [('b', 'c'), ('c', 'd')], 'expected the edges weighing more than 2'
+END
+# expected to fail, naming the record and the field which is not an integer
+cargo run --release -- --source ./example/jsoninput/mistyped.amo --no-writeback
@@ -0,0 +1,25 @@
+python ./example/limit/generate.py
+# expected to fail rather than to loop forever
+cargo run --release -- --source ./example/limit/limit.amo --no-writeback --max-iterations 100 > ./example/limit/limit.txt
+# a limit above the iterations of a terminating program does not change its answer,
+# a limit below them aborts it
+cargo run --release -- --source ./example/limit/bounded.amo --db ./example/limit/limit.db --no-writeback --max-iterations 100 > ./example/limit/bounded.txt
+cargo run --release -- --source ./example/limit/bounded.amo --db ./example/limit/limit.db --no-writeback --max-iterations 5 > ./example/limit/bounded_5.txt
+# expected to fail, a limit is a number of iterations
+cargo run --release -- --source ./example/limit/limit.amo --no-writeback --max-iterations many > ./example/limit/invalid.txt 2>&1
+python - <<'END'
+output = open('./example/limit/limit.txt').read()
+print(output)
+assert 'ERROR: counter has not reached a fixpoint after 100 iterations' in output
+assert 'COUNT' not in output, 'expected no tuple to be printed'
+output = open('./example/limit/bounded.txt').read()
+print(output)
+assert 'ERROR' not in output and 'COUNT: 10' in output, 'expected the ten values of counter'
+output = open('./example/limit/bounded_5.txt').read()
+print(output)
+assert 'ERROR: counter has not reached a fixpoint after 5 iterations' in output
+assert 'COUNT' not in output, 'expected no tuple to be printed'
+output = open('./example/limit/invalid.txt').read()
+print(output)
+assert "invalid value 'many' for '--max-iterations <N>'" in output
+END
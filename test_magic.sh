@@ -0,0 +1,39 @@
+python ./example/magic/generate.py
+# the answer of a query with constants is the same with and without magic sets
+cargo run --release -- --source ./example/magic/magic.amo --no-writeback --format json > ./example/magic/full.json
+cargo run --release -- --source ./example/magic/magic.amo --no-writeback --format json --magic > ./example/magic/magic.json
+python - <<'END'
+import json
+def load(path):
+    results = json.load(open(path))
+    return {query: sorted(json.dumps(entity, sort_keys=True) for entity in entities) for query, entities in results.items()}
+full = load('./example/magic/full.json')
+magic = load('./example/magic/magic.json')
+print('QUERY: reachable(0, Y)')
+print('COUNT: {} (full), {} (magic)'.format(len(full['reachable']), len(magic['reachable'])))
+assert full == magic, 'magic sets give a different answer'
+END
+# a nonlinear rule and a left-linear rule queried on its last argument give the same answer with magic sets
+for name in nonlinear left_linear; do
+    cargo run --release -- --source ./example/magic/$name.amo --no-writeback --format json > ./example/magic/${name}_full.json
+    cargo run --release -- --source ./example/magic/$name.amo --no-writeback --format json --magic > ./example/magic/${name}_magic.json
+done
+python - <<'END'
+import json
+def load(path):
+    results = json.load(open(path))
+    return {query: sorted(json.dumps(entity, sort_keys=True) for entity in entities) for query, entities in results.items()}
+for name, expected in [('nonlinear', ['a', 'b', 'c', 'd']), ('left_linear', ['a', 'b', 'c', 'x'])]:
+    full = load('./example/magic/{}_full.json'.format(name))
+    magic = load('./example/magic/{}_magic.json'.format(name))
+    assert full == magic, 'magic sets give a different answer for {}'.format(name)
+    column = 'column_1' if name == 'nonlinear' else 'column_0'
+    found = sorted(json.loads(entity)[column] for entity in magic['path'])
+    assert found == expected, 'expected {} for {}, found {}'.format(expected, name, found)
+END
+# expected to fail, a predicate cannot collide with the predicates introduced by magic sets
+cargo run --release -- --source ./example/magic/reserved.amo --no-writeback --magic > ./example/magic/reserved.txt
+python - <<'END'
+output = open('./example/magic/reserved.txt').read()
+assert 'Predicate seed_node starts with seed_, which is reserved for the tables of the evaluation' in output
+END
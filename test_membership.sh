@@ -0,0 +1,12 @@
+# `X in (a, b, c)` holds if X is one of the constants
+cargo run --release -- --source ./example/membership/membership.amo --no-writeback --format json > ./example/membership/membership.json
+python - <<'END'
+import json
+result = json.load(open('./example/membership/membership.json'))
+rows = lambda name: sorted(row['column_0'] for row in result[name])
+assert rows('rgb') == ['blue', 'green', 'red'], 'expected the colors in (red, green, blue)'
+assert rows('odd') == ['blue', 'red', 'white'], 'expected the colors numbered in (1, 3, 9)'
+assert rows('other') == ['black', 'white'], 'expected the colors not in (red, green, blue)'
+END
+# expected to fail, a symbol is not comparable with an integer
+cargo run --release -- --source ./example/membership/mismatch.amo --no-writeback
@@ -0,0 +1,12 @@
+# an idb mixing a symbol key with computed columns is typed column by column
+cargo run --release -- --source ./example/mixed/mixed.amo --no-writeback --explain > ./example/mixed/explain.txt
+cargo run --release -- --source ./example/mixed/mixed.amo --no-writeback --format json > ./example/mixed/mixed.json
+python - <<'END'
+import json
+explain = open('./example/mixed/explain.txt').read()
+columns = 'column_0 TEXT, column_1 TEXT, column_2 INTEGER, column_3 REAL, column_4 BOOLEAN, column_5 TEXT'
+assert 'CREATE TABLE IF NOT EXISTS priced ({},'.format(columns) in explain, 'expected priced to be typed as {}'.format(columns)
+priced = {row['column_0']: row for row in json.load(open('./example/mixed/mixed.json'))['priced']}
+assert priced['fig'] == {'column_0': 'fig', 'column_1': 'fig', 'column_2': 20, 'column_3': 1.0, 'column_4': True, 'column_5': 'fruit'}
+assert priced['pear']['column_4'] is False and isinstance(priced['pear']['column_2'], int)
+END
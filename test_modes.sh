@@ -0,0 +1,12 @@
+cargo run --release -- --source ./example/modes/modes.amo --no-writeback
+# the iteration of reach is the base case of step, reading delta_reach instead of reach
+cargo run --release -- --source ./example/modes/modes.amo --explain > ./example/modes/explain.sql
+python - <<'END'
+lines = open('./example/modes/explain.sql').read().splitlines()
+statements = [statement.strip() for statement in '\n'.join(line for line in lines if not line.startswith('--')).split(';')]
+iteration = [statement for statement in statements if statement.startswith('INSERT OR IGNORE INTO temp_reach(')][0]
+base = [statement for statement in statements if statement.startswith('INSERT OR IGNORE INTO step(')][0]
+print(iteration)
+assert 'delta_reach' in iteration, 'the iteration should read delta_reach'
+assert iteration.replace('temp_reach', 'step').replace('delta_reach', 'reach') == base, 'the base case and the iteration differ'
+END
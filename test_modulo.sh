@@ -0,0 +1,21 @@
+# `X % 2 == 0` holds for the even numbers
+cargo run --release -- --source ./example/modulo/modulo.amo --no-writeback --format json > ./example/modulo/modulo.json
+python - <<'END'
+import json
+result = json.load(open('./example/modulo/modulo.json'))
+assert sorted(row['column_0'] for row in result['even']) == [2, 4, 10], 'expected the even numbers'
+buckets = sorted((row['column_0'], row['column_1']) for row in result['bucket'])
+assert buckets == [(1, 1), (2, 2), (3, 0), (4, 1), (7, 1), (10, 1)], 'expected the remainders by 3'
+END
+# expected to fail, a modulo is only taken between integers
+cargo run --release -- --source ./example/modulo/float.amo --no-writeback
+# a `%` which is not followed by an operand on its line starts a comment, even after an expression
+cargo run --release -- --source ./example/modulo/comment.amo --no-writeback --format json > ./example/modulo/comment.json
+python - <<'END'
+import json
+result = json.load(open('./example/modulo/comment.json'))
+assert sorted(row['column_0'] for row in result['big']) == [4, 7, 10], 'expected the trailing comment to be skipped'
+assert sorted(row['column_0'] for row in result['odd']) == [1, 3, 7], 'expected the odd numbers'
+pairs = sorted((row['column_0'], row['column_1']) for row in result['pair'])
+assert pairs == [(1, 2), (2, 0), (3, 1), (4, 2), (7, 2), (10, 2)], 'expected the remainders by a parenthesized operand'
+END
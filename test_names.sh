@@ -0,0 +1,17 @@
+# every subgoal on an undefined predicate is reported at once, with the rule it occurs in
+cargo run --release -- --source ./example/names/undefined.amo --no-writeback > ./example/names/stdout.txt
+python - <<'END'
+output = open('./example/names/stdout.txt').read()
+print(output)
+for undefined in ['egde in subgoal 2 of path(X, Z)', 'pth in subgoal 1 of cycle(X)', 'blocked in subgoal 2 of pair(X, Y)']:
+    assert undefined in output, 'expected {} to be reported'.format(undefined)
+END
+# expected to fail, a predicate declared by @input is also derived by rules
+cargo run --release -- --source ./example/names/input_rule.amo --no-writeback > ./example/names/input_rule.txt
+cargo run --release -- --source ./example/names/input_above_rule.amo --no-writeback > ./example/names/input_above_rule.txt
+python - <<'END'
+for name in ['input_rule', 'input_above_rule']:
+    output = open('./example/names/{}.txt'.format(name)).read()
+    print(output)
+    assert 'Predicate reachable is declared by @input, but is also derived by the rule reachable(X, Y) :- edge(X, Y)' in output, name
+END
@@ -0,0 +1,31 @@
+# idbs are written back as idb_<predicate>, leaving the tables of edbs untouched
+python - <<'END'
+import sqlite3, os
+if os.path.exists('./example/namespace/path.db'):
+    os.remove('./example/namespace/path.db')
+database = sqlite3.connect('./example/namespace/path.db')
+database.execute('CREATE TABLE edge (column_0 TEXT, column_1 TEXT)')
+database.executemany('INSERT INTO edge VALUES (?, ?)', [('a', 'b'), ('b', 'c'), ('c', 'd'), ('d', 'e')])
+database.commit()
+END
+cargo run --release -- --source ./example/namespace/path.amo --writeback-idbs
+python - <<'END'
+import sqlite3
+database = sqlite3.connect('./example/namespace/path.db')
+tables = sorted(row[0] for row in database.execute("SELECT name FROM sqlite_master WHERE type = 'table'"))
+assert tables == ['edge', 'idb_path'], 'expected only edge and idb_path, found {}'.format(tables)
+assert database.execute('SELECT COUNT(*) FROM edge').fetchone()[0] == 4
+assert database.execute('SELECT COUNT(*) FROM idb_path').fetchone()[0] == 10
+END
+# an evaluation interrupted by an error in the middle of a fixpoint drops its delta_ and temp_ tables
+cargo run --release -- --source ./example/namespace/path.amo --no-writeback --max-iterations 2 --verbose > ./example/namespace/interrupted.txt
+python - <<'END'
+import re
+output = open('./example/namespace/interrupted.txt').read()
+assert 'has not reached a fixpoint after 2 iterations' in output
+created = re.findall(r'CREATE TABLE (?:IF NOT EXISTS )?((?:delta|temp)_\w+)', output)
+dropped = re.findall(r'DROP TABLE ((?:delta|temp)_\w+)', output)
+assert created and sorted(created) == sorted(dropped), 'expected {} to be dropped, found {}'.format(created, dropped)
+END
+# expected to fail, a predicate cannot start with a reserved prefix
+cargo run --release -- --source ./example/namespace/reserved.amo --no-writeback
@@ -0,0 +1,2 @@
+python ./example/negation/generate.py
+cargo run --release -- --source ./example/negation/negation.amo --verbose
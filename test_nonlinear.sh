@@ -0,0 +1,16 @@
+# a closure with the recursive predicate twice in its body derives the same relation as a linear closure
+cargo run --release -- --source ./example/nonlinear/tc.amo --no-writeback --format json > ./example/nonlinear/sqlite.json
+cargo run --release -- --source ./example/nonlinear/tc.amo --no-writeback --format json --backend memory > ./example/nonlinear/memory.json
+cargo run --release -- --source ./example/nonlinear/tc.amo --no-writeback --explain > ./example/nonlinear/explain.txt
+python - <<'END'
+import json
+for backend in ['sqlite', 'memory']:
+    result = json.load(open('./example/nonlinear/{}.json'.format(backend)))
+    pairs = lambda name: sorted((row['column_0'], row['column_1']) for row in result[name])
+    assert len(pairs('linear')) == 143, 'expected every pair of the closure'
+    assert pairs('nonlinear') == pairs('linear'), 'expected the same closure from both rules with ' + backend
+explain = open('./example/nonlinear/explain.txt').read()
+# an iteration joins the delta with the full relation, and the full relation with the delta
+assert 'FROM delta_nonlinear AS b0\n         JOIN nonlinear AS b1' in explain, 'expected delta joined with full'
+assert 'FROM nonlinear AS b0\n         JOIN delta_nonlinear AS b1' in explain, 'expected full joined with delta'
+END
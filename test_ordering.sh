@@ -0,0 +1,3 @@
+cargo run --release -- --source ./example/ordering/ordering.amo --no-writeback
+# expected to fail, an integer is compared with a symbol
+cargo run --release -- --source ./example/ordering/mismatch.amo --no-writeback
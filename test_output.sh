@@ -0,0 +1,25 @@
+# @output() prints a preview of at most 20 numbers, @output(numbers) writes all of them to numbers.csv
+cargo run --release -- --source ./example/output/output.amo --no-writeback > ./example/output/stdout.txt
+python - <<'END'
+lines = open('./example/output/stdout.txt').read().splitlines()
+print('\n'.join(lines))
+preview = lines[1:lines.index('COUNT: 30')]
+assert preview == [str(i) for i in range(10)] + ['...'] + [str(i) for i in range(20, 30)], 'expected the first and last 10 numbers'
+rows = open('./example/output/numbers.csv').read().splitlines()
+assert rows[0] == 'column_0', 'expected a header row'
+assert sorted(int(row) for row in rows[1:]) == list(range(30)), 'expected every number in numbers.csv'
+END
+# --preview 3 prints the first and last 3 numbers, --preview 15 every number, and --preview 0 only the count
+cargo run --release -- --source ./example/output/output.amo --no-writeback --sorted --preview 3 > ./example/output/preview.txt
+cargo run --release -- --source ./example/output/output.amo --no-writeback --sorted --preview 15 > ./example/output/all.txt
+cargo run --release -- --source ./example/output/output.amo --no-writeback --sorted --preview 0 > ./example/output/count.txt
+python - <<'END'
+preview = lambda name: open('./example/output/{}.txt'.format(name)).read().splitlines()
+lines = preview('preview')
+print('\n'.join(lines))
+assert lines[1:lines.index('COUNT: 30')] == ['0', '1', '2', '...', '27', '28', '29'], 'expected the first and last 3 numbers'
+lines = preview('all')
+assert lines[1:lines.index('COUNT: 30')] == [str(i) for i in range(30)], 'expected every number within twice the preview'
+lines = preview('count')
+assert lines[1] == 'COUNT: 30', 'expected no number printed by a preview of 0'
+END
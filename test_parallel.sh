@@ -0,0 +1,14 @@
+# the independent closures are evaluated in parallel threads,
+# and reach the same tuples as when they are evaluated one after the other
+cargo run --release -- --source ./example/parallel/closures.amo --no-writeback --sorted > ./example/parallel/serial.txt
+cargo run --release -- --source ./example/parallel/closures.amo --no-writeback --sorted --parallel > ./example/parallel/parallel.txt
+cargo run --release -- --source ./example/parallel/closures.amo --no-writeback --parallel --verbose > ./example/parallel/verbose.txt
+python - <<'END'
+serial = open('./example/parallel/serial.txt').read()
+parallel = open('./example/parallel/parallel.txt').read()
+verbose = open('./example/parallel/verbose.txt').read().splitlines()
+print(parallel)
+assert parallel == serial, 'expected the same tuples as the serial evaluation'
+assert any(line.endswith('PARALLEL: drive, ride') for line in verbose), 'expected drive and ride to be evaluated in parallel'
+assert 'COUNT: 6' in parallel, 'expected both to join the closures'
+END
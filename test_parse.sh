@@ -0,0 +1,10 @@
+# expected to fail, a parsing error is reported at its line and column, with a caret under the column
+cargo run --release -- --source ./example/parse/missing_term.amo --no-writeback > ./example/parse/missing_term.txt
+python - <<'END'
+output = open('./example/parse/missing_term.txt').read()
+print(output)
+lines = output.splitlines()
+assert lines[0] == 'ERROR: ./example/parse/missing_term.amo: Parsing error at line 1, column 9:', lines[0]
+assert lines[1] == 'edge(1, ).'
+assert lines[2] == ' ' * 8 + '^', 'expected the caret under the missing term'
+END
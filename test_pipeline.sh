@@ -0,0 +1,11 @@
+# the @output of a program is read as the @input of the next one
+cargo run --release -- --source ./example/pipeline/produce.amo --no-writeback
+cargo run --release -- --source ./example/pipeline/consume/consume.amo --no-writeback --format json > ./example/pipeline/consume/hub.json
+python - <<'END'
+import json
+result = json.load(open('./example/pipeline/consume/hub.json'))
+hubs = sorted(row['column_0'] for row in result['hub'])
+assert hubs == ['a', 'b'], 'expected the nodes reaching more than one node in the closure of the first stage'
+END
+# expected to fail, the header of the csv does not match the declared columns
+cargo run --release -- --source ./example/pipeline/consume/renamed.amo --no-writeback
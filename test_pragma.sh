@@ -0,0 +1,14 @@
+# the pragmas are applied to the in-memory database, and a large closure is still complete
+python ./example/chain/generate.py
+cargo run --release -- --source ./example/chain/chain.amo --no-writeback --verbose --cache-mb 64 --journal-mode off > ./example/chain/pragma.txt
+python - <<'END'
+lines = open('./example/chain/pragma.txt').read().splitlines()
+pragmas = dict(line.split(': ', 1)[1].split(' = ') for line in lines if line.startswith('PRAGMA: '))
+print(pragmas)
+assert pragmas['cache_size'] == str(-64 * 1024), 'expected a cache of 64 MiB'
+assert pragmas['temp_store'] == '2', 'expected temporary tables in memory'
+assert pragmas['journal_mode'] == 'off'
+assert 'COUNT: {}'.format(200 * 201 // 2) in lines, 'expected every pair of the chain'
+rows = open('./example/chain/path.csv').read().splitlines()
+assert len(set(rows[1:])) == 200 * 201 // 2
+END
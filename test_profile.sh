@@ -0,0 +1,14 @@
+# profiling the statements prints their time by kind after the queries, without changing them
+cargo run --release -- --source ./example/profile/reach.amo --no-writeback --sorted > ./example/profile/plain.txt
+cargo run --release -- --source ./example/profile/reach.amo --no-writeback --sorted --profile-sql > ./example/profile/profile.txt
+python - <<'END'
+plain = open('./example/profile/plain.txt').read().splitlines()
+lines = open('./example/profile/profile.txt').read().splitlines()
+profile = lines[lines.index('PROFILE'):]
+print('\n'.join(profile))
+assert lines[:lines.index('PROFILE')] == plain, 'expected the same queries with and without profiling'
+kinds = {line.split()[0]: int(line.split()[1]) for line in profile[2:]}
+assert {'create', 'base-insert', 'delta-join', 'diff', 'merge'} <= set(kinds), 'expected every kind of statement'
+assert kinds['base-insert'] == 1, 'expected a single base case'
+assert kinds['delta-join'] == kinds['diff'] == kinds['merge'], 'expected a join, a diff and a merge by iteration'
+END
@@ -0,0 +1,10 @@
+python ./example/projection/generate.py
+cargo run --release -- --source ./example/projection/projection.amo --verbose
+# expected to fail with a free variable in the head
+cargo run --release -- --source ./example/projection/free_head.amo > ./example/projection/free_head.txt
+python - <<'END'
+output = open('./example/projection/free_head.txt').read()
+print(output)
+assert 'ERROR: Rule of src has `_` in its head' in output, 'expected the wildcard in the head to be reported'
+assert 'panicked' not in output and 'QUERY' not in output
+END
@@ -0,0 +1,4 @@
+# only path is evaluated, counter would never reach a fixpoint
+cargo run --release -- --source ./example/query/query.amo --no-writeback --query path --stats
+# expected to fail, reachable is not an @output predicate
+cargo run --release -- --source ./example/query/query.amo --no-writeback --query reachable
@@ -0,0 +1 @@
+cargo run --release -- --source ./example/quoted/quoted.amo --verbose --no-writeback
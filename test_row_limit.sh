@@ -0,0 +1,13 @@
+# --limit bounds the printed tuples of a query, after they are sorted,
+# while the relation written back to the database is complete
+cargo run --release -- --source ./example/row_limit/chain.amo --sorted --limit 5 > ./example/row_limit/limited.txt
+python - <<'END'
+import sqlite3
+lines = open('./example/row_limit/limited.txt').read().splitlines()
+print('\n'.join(lines))
+rows = lines[lines.index('QUERY: reach(X, Y)') + 1:lines.index('COUNT: 5')]
+assert rows == ['1, 2', '1, 3', '1, 4', '1, 5', '1, 6'], 'expected the first 5 tuples of the sorted closure'
+database = sqlite3.connect('./example/row_limit/chain.db')
+count = database.execute('SELECT COUNT(*) FROM reach').fetchone()[0]
+assert count == 45, 'expected the whole closure to be derived'
+END
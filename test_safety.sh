@@ -0,0 +1,7 @@
+cargo run --release -- --source ./example/safety/safe.amo --no-writeback
+# expected to fail with an unsafe variable in the head
+cargo run --release -- --source ./example/safety/head.amo
+# expected to fail with an unsafe variable in an arithmetic subgoal
+cargo run --release -- --source ./example/safety/arith.amo
+# expected to fail with an unsafe variable in a negated subgoal
+cargo run --release -- --source ./example/safety/negation.amo
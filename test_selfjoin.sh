@@ -0,0 +1,2 @@
+python ./example/selfjoin/generate.py
+cargo run --release -- --source ./example/selfjoin/selfjoin.amo --verbose
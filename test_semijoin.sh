@@ -0,0 +1,19 @@
+# a guard atom is a semi-join, and each hop is derived once however many tags its target has
+cargo run --release -- --source ./example/semijoin/semijoin.amo --no-writeback --explain > ./example/semijoin/explain.sql
+cargo run --release -- --source ./example/semijoin/semijoin.amo --no-writeback --format json > ./example/semijoin/semijoin.json
+python - <<'END'
+import json
+explain = open('./example/semijoin/explain.sql').read()
+print(explain)
+assert 'WHERE EXISTS (SELECT 1 FROM tagged AS b1' in explain, 'expected tagged to be a semi-join'
+assert 'JOIN tagged' not in explain, 'expected tagged not to be joined'
+hop = json.load(open('./example/semijoin/semijoin.json'))['hop']
+assert sorted((row['column_0'], row['column_1']) for row in hop) == [('a', 'b'), ('a', 'c'), ('b', 'c')]
+END
+# an atom of an aggregating rule is never a semi-join, each of its tuples is counted
+cargo run --release -- --source ./example/semijoin/degree.amo --no-writeback --format json > ./example/semijoin/degree.json
+python - <<'END'
+import json
+degree = json.load(open('./example/semijoin/degree.json'))['degree']
+assert sorted((row['column_0'], row['column_1']) for row in degree) == [('a', 3), ('b', 1)], degree
+END
@@ -0,0 +1 @@
+cargo run --release -- --source ./example/shortest/shortest.amo --no-writeback
@@ -0,0 +1,21 @@
+# --sorted prints every query ordered by its columns, integers numerically, and the same across runs
+cargo run --release -- --source ./example/sorted/sorted.amo --no-writeback --sorted > ./example/sorted/first.txt
+cargo run --release -- --source ./example/sorted/sorted.amo --no-writeback --sorted > ./example/sorted/second.txt
+python - <<'END'
+first = open('./example/sorted/first.txt').read()
+print(first)
+assert first == open('./example/sorted/second.txt').read(), 'expected the same output across runs'
+lines = first.splitlines()
+pair = lines[lines.index('QUERY: pair(N, S)') + 1:][:6]
+assert pair == ['1, z', '2, a', '2, b', '10, j', '33, a', '100, c'], 'expected integers ordered numerically'
+assert lines[lines.index('QUERY: large(S, N)') + 1:][:3] == ['a, 33', 'c, 100', 'j, 10']
+# a query written to a file is ordered without --sorted
+rows = open('./example/sorted/pairs.csv').read().splitlines()
+assert rows[1:] == ['1,z', '2,a', '2,b', '10,j', '33,a', '100,c']
+END
+rm ./example/sorted/pairs.csv
+cargo run --release -- --source ./example/sorted/sorted.amo --no-writeback
+python - <<'END'
+rows = open('./example/sorted/pairs.csv').read().splitlines()
+assert rows[1:] == ['1,z', '2,a', '2,b', '10,j', '33,a', '100,c'], 'expected pairs.csv to be ordered'
+END
@@ -0,0 +1,10 @@
+# transitive closure of a chain takes as many iterations as the chain has edges
+cargo run --release -- --source ./example/stats/chain.amo --no-writeback --stats > ./example/stats/stats.txt
+python - <<'END'
+lines = open('./example/stats/stats.txt').read().splitlines()
+stats = lines[lines.index('STATS') + 2:]
+path = [line.split(None, 2) for line in stats if line.split()[0] == 'path'][0]
+print('\n'.join(lines[lines.index('STATS'):]))
+assert path[1] == '4', 'expected 4 iterations of path'
+assert path[2] == '3, 2, 1, 0', 'expected deltas of 3, 2, 1 and 0 tuples'
+END
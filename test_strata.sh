@@ -0,0 +1,13 @@
+python ./example/strata/generate.py
+cargo run --release -- --source ./example/strata/strata.amo --verbose
+# expected to fail, win and lose form a cycle through a negation
+cargo run --release -- --source ./example/strata/cycle.amo --no-writeback
+# the strata are listed in evaluation order, with reachable marked recursive
+cargo run --release -- --source ./example/strata/levels.amo --no-writeback --show-strata > ./example/strata/levels.txt
+python - <<'END'
+output = open('./example/strata/levels.txt').read().splitlines()
+print('\n'.join(output))
+strata = output[output.index('STRATA') + 1:output.index('STRATA') + 4]
+assert strata == ['stratum 0: edge', 'stratum 1: reachable (recursive)', 'stratum 2: unreachable'], strata
+assert 'COUNT: 1' in output, 'expected the program to be evaluated after the strata are listed'
+END
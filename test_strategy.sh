@@ -0,0 +1,17 @@
+# the naive strategy derives every tuple again at each iteration, and is an oracle for the semi-naive one,
+# both reach the same fixpoint after as many iterations
+for program in strategy/paths closure/closure rsg/rsg negation/negation diff/cycle parallel/closures; do
+    name=$(basename $program)
+    cargo run --release -- --source ./example/$program.amo --no-writeback --sorted --stats --strategy semi-naive > ./example/strategy/$name.semi-naive.txt
+    cargo run --release -- --source ./example/$program.amo --no-writeback --sorted --stats --strategy naive > ./example/strategy/$name.naive.txt
+done
+python - <<'END'
+import glob
+for semi_naive in sorted(glob.glob('./example/strategy/*.semi-naive.txt')):
+    naive = semi_naive.replace('.semi-naive.txt', '.naive.txt')
+    # predicates of a stratum are evaluated in any order
+    split = lambda lines: (lines[:lines.index('STATS')], sorted(lines[lines.index('STATS'):]))
+    lines = open(semi_naive).read().splitlines(), open(naive).read().splitlines()
+    assert not any(line.startswith('ERROR') for line in lines[0]), semi_naive
+    assert split(lines[0]) == split(lines[1]), 'expected the same tuples and iterations in ' + naive
+END
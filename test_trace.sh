@@ -0,0 +1,26 @@
+# the derivation of reachable(a, d) follows the 3 edges of the path
+cargo run --release -- --source ./example/trace/closure.amo --no-writeback --trace reachable,a,d > ./example/trace/trace.txt
+python - <<'END'
+lines = open('./example/trace/trace.txt').read().splitlines()
+trace = lines[lines.index('TRACE: reachable(a, d)') + 1:]
+print('\n'.join(trace))
+assert trace == [
+    'reachable(a, d) by rule 2',
+    '  reachable(a, c) by rule 2',
+    '    reachable(a, b) by rule 1',
+    '      edge(a, b)',
+    '    edge(b, c)',
+    '  edge(c, d)',
+], 'expected a derivation over the edges a -> b -> c -> d'
+END
+# reachable(d, a) is not derived
+cargo run --release -- --source ./example/trace/closure.amo --no-writeback --trace reachable,d,a
+# expected to fail, reachable has 2 columns
+cargo run --release -- --source ./example/trace/closure.amo --no-writeback --trace reachable,a
+# expected to fail, NaN is not a value of the float column of weight
+cargo run --release -- --source ./example/trace/weight.amo --no-writeback --trace weight,a,nan > ./example/trace/nan.txt
+python - <<'END'
+output = open('./example/trace/nan.txt').read()
+print(output)
+assert 'Cannot trace weight,a,nan: `nan` is not a valid Float' in output, 'expected the traced tuple to be rejected'
+END
@@ -0,0 +1,6 @@
+python ./example/closure/generate.py
+output=$(cargo run --release -- --source ./example/closure/closure.amo --verbose --no-writeback --bench)
+# each semi-naive iteration is committed once, rather than once per statement
+echo "statements: $(echo "$output" | grep -c EXECUTE)"
+echo "commits: $(echo "$output" | grep -c COMMIT)"
+echo "$output" | tail -n 1
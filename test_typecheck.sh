@@ -0,0 +1,18 @@
+# arithmetic is type-checked before evaluation, the offending expression is reported
+cargo run --release -- --source ./example/typecheck/typed.amo --no-writeback --format json > ./example/typecheck/typed.json
+cargo run --release -- --source ./example/typecheck/add.amo --no-writeback > ./example/typecheck/stdout.txt
+cargo run --release -- --source ./example/typecheck/compare.amo --no-writeback >> ./example/typecheck/stdout.txt
+cargo run --release -- --source ./example/typecheck/minus.amo --no-writeback >> ./example/typecheck/stdout.txt
+python - <<'END'
+import json
+cheap = json.load(open('./example/typecheck/typed.json'))['cheap']
+assert sorted(row['column_0'] for row in cheap) == ['apple', 'fig']
+output = open('./example/typecheck/stdout.txt').read()
+print(output)
+for error in [
+    "Integer and Symbol are incompatible in `Q + 'hello'` in the body of label",
+    'Integer and Symbol are incompatible in `((Q * 2) + 1) > X` in the body of large',
+    'Symbol is not a number in `-X` in the body of small',
+]:
+    assert error in output, 'expected {} to be reported'.format(error)
+END
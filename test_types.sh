@@ -0,0 +1,24 @@
+python ./example/types/generate.py
+cargo run --release -- --source ./example/types/types.amo --verbose
+# expected to fail with an unknown column type
+cargo run --release -- --source ./example/types/unknown.amo --no-writeback
+# constants in the head are typed by their own value
+cargo run --release -- --source ./example/types/constant.amo --no-writeback --format json > ./example/types/constant.json
+python - <<'END'
+import json
+results = json.load(open('./example/types/constant.json'))
+status = {row['column_0']: row for row in results['status']}
+assert status['alice']['column_1'] == 'adult' and status['alice']['column_2'] == 1
+assert status['bob']['column_1'] == 'minor' and status['bob']['column_2'] == 0
+weight = {row['column_0']: row['column_1'] for row in results['weight']}
+assert weight == {'alice': 1.0, 'bob': 0.5}
+assert all(isinstance(value, float) for value in weight.values()), 'weight should be a float column'
+END
+# expected to fail, base cases typing a column as a symbol and as an integer
+cargo run --release -- --source ./example/types/mixed.amo --no-writeback > ./example/types/mixed.txt
+python - <<'END'
+output = open('./example/types/mixed.txt').read()
+print(output)
+assert 'ERROR: Symbol and Integer are incompatible in `t(X) :- n(X)` in the body of t' in output
+assert 'QUERY' not in output, 'expected nothing to be evaluated'
+END
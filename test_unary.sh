@@ -0,0 +1,10 @@
+# `!` negates a boolean, e.g. a parenthesized comparison
+cargo run --release -- --source ./example/unary/unary.amo --no-writeback --format json > ./example/unary/unary.json
+python - <<'END'
+import json
+result = json.load(open('./example/unary/unary.json'))
+rows = lambda name: sorted((row['column_0'], row['column_1']) for row in result[name])
+assert rows('not_less') == rows('at_least') == [(2, 2), (3, 2), (7, -1)], 'expected !(X < Y) to hold exactly when X >= Y'
+END
+# expected to fail, only a boolean is negated
+cargo run --release -- --source ./example/unary/numeric.amo --no-writeback
@@ -0,0 +1,5 @@
+cargo run --release -- --source ./example/view/view.amo --no-writeback
+cargo run --release -- --source ./example/view/view.amo --no-writeback --magic
+cargo run --release -- --source ./example/view/view.amo --no-writeback --explain
+# expected to fail, a view is named after an idb
+cargo run --release -- --source ./example/view/shadow.amo --no-writeback
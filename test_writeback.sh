@@ -0,0 +1,6 @@
+python ./example/negation/generate.py
+before=$(sha256sum ./example/negation/negation.db)
+cargo run --release -- --source ./example/negation/negation.amo --no-writeback
+after=$(sha256sum ./example/negation/negation.db)
+# the source database should be left untouched
+if [ "$before" = "$after" ]; then echo "UNCHANGED"; else echo "CHANGED"; exit 1; fi